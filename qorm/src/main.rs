@@ -126,15 +126,14 @@ impl<M> DerefMut for BelongsTo<M> {
 }
 
 impl<M: Model> BelongsTo<M> {
-    pub async fn first<DB, E>(mut self, executor: E) -> Result<M, sqlx::Error>
+    pub async fn first<DB, E>(self, executor: E) -> Result<M, sqlx::Error>
     where
         DB: sqlx::Database + HasDialect,
         M: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
         E: for<'c> sqlx::Executor<'c, Database = DB>,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.builder.bindings_mut().take();
-        let sql = self.to_sql::<DB>();
+        let (sql, bindings) = self.builder.build_sql::<DB>();
         sqlx::query_as_with::<_, M, _>(&sql, bindings)
             .fetch_one(executor)
             .await
@@ -185,15 +184,14 @@ impl<M> DerefMut for HasOne<M> {
 }
 
 impl<M: Model> HasOne<M> {
-    pub async fn first<DB, E>(mut self, executor: E) -> Result<M, sqlx::Error>
+    pub async fn first<DB, E>(self, executor: E) -> Result<M, sqlx::Error>
     where
         DB: sqlx::Database + HasDialect,
         M: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
         E: for<'c> sqlx::Executor<'c, Database = DB>,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.builder.bindings_mut().take();
-        let sql = self.to_sql::<DB>();
+        let (sql, bindings) = self.builder.build_sql::<DB>();
         sqlx::query_as_with::<_, M, _>(&sql, bindings)
             .fetch_one(executor)
             .await
@@ -480,9 +478,8 @@ pub async fn main() -> eyre::Result<()> {
 
     println!("first team {:?}", team);
 
-    let mut user = team.user();
-    let sql = user.to_sql::<MySql>();
-    let bindings = user.bindings();
+    let user = team.user();
+    let (sql, bindings) = user.build_sql::<MySql>();
 
     // select * from teams where teams.id = users.team_id
     //println!("SQL: {} and binds {:?}", sql, bindings);
@@ -0,0 +1,97 @@
+use heck::{ToKebabCase, ToLowerCamelCase, ToPascalCase, ToShoutySnakeCase, ToSnakeCase};
+use syn::{Attribute, Error, Field, LitStr};
+
+// every derive shares the `qraft` attribute, so each one accepts all the
+// keys and only acts on the ones it knows about
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub table: Option<String>,
+    pub rename_all: Option<LitStr>,
+}
+
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    pub skip: bool,
+    pub skip_if_none: bool,
+    pub default: bool,
+}
+
+pub fn container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut container_attrs = ContainerAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("qraft")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                let value: LitStr = meta.value()?.parse()?;
+                container_attrs.table = Some(value.value());
+                Ok(())
+            } else if meta.path.is_ident("rename_all") {
+                let value: LitStr = meta.value()?.parse()?;
+                // validated early so the error points at the attribute
+                rename_case("", &value)?;
+                container_attrs.rename_all = Some(value);
+                Ok(())
+            } else {
+                Err(meta.error("expected `table` or `rename_all`"))
+            }
+        })?;
+    }
+    Ok(container_attrs)
+}
+
+pub fn field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("qraft")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: LitStr = meta.value()?.parse()?;
+                field_attrs.rename = Some(value.value());
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                field_attrs.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("skip_if_none") {
+                field_attrs.skip_if_none = true;
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                field_attrs.default = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `rename`, `skip`, `skip_if_none` or `default`"))
+            }
+        })?;
+    }
+    Ok(field_attrs)
+}
+
+/// The column a field maps to, after `rename` and `rename_all`.
+pub fn column_name(
+    field: &Field,
+    field_attrs: &FieldAttrs,
+    container_attrs: &ContainerAttrs,
+) -> syn::Result<String> {
+    if let Some(ref rename) = field_attrs.rename {
+        return Ok(rename.clone());
+    }
+    let name = field.ident.as_ref().expect("named field").to_string();
+    let name = name.trim_start_matches("r#");
+    match container_attrs.rename_all {
+        Some(ref case) => rename_case(name, case),
+        None => Ok(name.to_string()),
+    }
+}
+
+// same case names as serde and sqlx
+fn rename_case(name: &str, case: &LitStr) -> syn::Result<String> {
+    let renamed = match case.value().as_str() {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "snake_case" => name.to_snake_case(),
+        "camelCase" => name.to_lower_camel_case(),
+        "PascalCase" => name.to_pascal_case(),
+        "SCREAMING_SNAKE_CASE" => name.to_shouty_snake_case(),
+        "kebab-case" => name.to_kebab_case(),
+        _ => return Err(Error::new_spanned(case, "unknown `rename_all` case")),
+    };
+    Ok(renamed)
+}
@@ -129,7 +129,7 @@ fn is_bindable_ignore(attr: &Attribute) -> bool {
     matches!(attr.parse_args::<Ident>(), Ok(ident) if ident == "ignore")
 }
 
-fn extract_inner_type_if_option(ty: &Type) -> Option<&Type> {
+pub(crate) fn extract_inner_type_if_option(ty: &Type) -> Option<&Type> {
     if let Type::Path(type_path) = ty {
         if let Some(last_seg) = type_path.path.segments.last() {
             if last_seg.ident == "Option" {
@@ -5,13 +5,35 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, ItemFn, Ident};
 
+mod attrs;
 mod bindable;
+mod row;
+mod table;
 
 #[proc_macro_derive(Bindable, attributes(bindable))]
 pub fn bindable_derive(input: TokenStream) -> TokenStream {
     bindable::bindable_derive_impl(input)
 }
 
+/// Implements `TableSchema` and `ProjectionSchema` from the struct fields.
+///
+/// Accepts `#[qraft(table = "...", rename_all = "...")]` on the struct and
+/// `#[qraft(rename = "...")]` or `#[qraft(skip)]` on the fields.
+#[proc_macro_derive(Table, attributes(qraft))]
+pub fn table_derive(input: TokenStream) -> TokenStream {
+    table::table_derive_impl(input)
+}
+
+/// Implements `IntoRow` from the struct fields.
+///
+/// Fields accept `#[qraft(rename = "...")]`, `#[qraft(skip)]`,
+/// `#[qraft(skip_if_none)]` and `#[qraft(default)]`, the latter renders the
+/// sql `default` when the value is `None` or for any non optional field.
+#[proc_macro_derive(IntoRow, attributes(qraft))]
+pub fn into_row_derive(input: TokenStream) -> TokenStream {
+    row::into_row_derive_impl(input)
+}
+
 #[proc_macro_attribute]
 pub fn or_variant(attr: TokenStream, item: TokenStream) -> TokenStream {
     let raw = attr.to_string();
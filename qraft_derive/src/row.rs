@@ -0,0 +1,95 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, parse_macro_input};
+
+use crate::{
+    attrs::{column_name, container_attrs, field_attrs},
+    bindable::extract_inner_type_if_option,
+};
+
+pub fn into_row_derive_impl(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+    let (impl_gen, ty_gen, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    struct_name,
+                    "`#[derive(IntoRow)]` requires a struct with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                struct_name,
+                "`#[derive(IntoRow)]` can only be applied to structs",
+            ));
+        }
+    };
+
+    let attrs = container_attrs(&input.attrs)?;
+    let mut assignments = Vec::new();
+    for field in fields {
+        let field_attrs = field_attrs(&field.attrs)?;
+        if field_attrs.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named field");
+        let column = column_name(field, &field_attrs, &attrs)?;
+        let column = quote! { ::qraft::ident::Ident::new_static(#column) };
+        let is_option = extract_inner_type_if_option(&field.ty).is_some();
+
+        if field_attrs.skip_if_none && !is_option {
+            return Err(Error::new_spanned(
+                &field.ty,
+                "`#[qraft(skip_if_none)]` requires an `Option` field",
+            ));
+        }
+
+        let assignment = if field_attrs.default && !is_option {
+            // a plain field has no missing value, the column always uses
+            // the sql default and updates leave it alone
+            quote! {
+                row.field(#column, ::qraft::default_value());
+            }
+        } else if field_attrs.default {
+            quote! {
+                match self.#ident {
+                    Some(value) => row.field(#column, value),
+                    None => row.field(#column, ::qraft::default_value()),
+                };
+            }
+        } else if field_attrs.skip_if_none {
+            quote! {
+                if let Some(value) = self.#ident {
+                    row.field(#column, value);
+                }
+            }
+        } else {
+            quote! {
+                row.field(#column, self.#ident);
+            }
+        };
+        assignments.push(assignment);
+    }
+
+    Ok(quote! {
+        impl #impl_gen ::qraft::row::IntoRow for #struct_name #ty_gen #where_clause {
+            fn into_row(self) -> ::qraft::Row {
+                let mut row = ::qraft::Row::new();
+                #(#assignments)*
+                row
+            }
+        }
+    })
+}
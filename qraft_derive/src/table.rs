@@ -0,0 +1,70 @@
+use heck::ToSnakeCase;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, parse_macro_input};
+
+use crate::attrs::{column_name, container_attrs, field_attrs};
+
+pub fn table_derive_impl(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+    let (impl_gen, ty_gen, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    struct_name,
+                    "`#[derive(Table)]` requires a struct with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                struct_name,
+                "`#[derive(Table)]` can only be applied to structs",
+            ));
+        }
+    };
+
+    let attrs = container_attrs(&input.attrs)?;
+    let table = attrs
+        .table
+        .clone()
+        .unwrap_or_else(|| struct_name.to_string().to_snake_case());
+
+    let mut columns = Vec::new();
+    for field in fields {
+        let field_attrs = field_attrs(&field.attrs)?;
+        if field_attrs.skip {
+            continue;
+        }
+        columns.push(column_name(field, &field_attrs, &attrs)?);
+    }
+    let len = columns.len();
+
+    Ok(quote! {
+        impl #impl_gen ::qraft::col::TableSchema for #struct_name #ty_gen #where_clause {
+            fn table() -> ::qraft::ident::Ident {
+                ::qraft::ident::Ident::new_static(#table)
+            }
+        }
+
+        impl #impl_gen ::qraft::col::ProjectionSchema for #struct_name #ty_gen #where_clause {
+            fn projections() -> ::qraft::col::Projections {
+                let columns: [::qraft::ident::Ident; #len] = [
+                    #(::qraft::ident::Ident::new_static(#columns)),*
+                ];
+                ::qraft::col::IntoProjections::into_select_proj(columns)
+            }
+        }
+    })
+}
@@ -1,15 +1,17 @@
-#[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
 use sqlx::{Arguments, IntoArguments};
 
-use crate::{expr::TakeBindings, writer::FormatWriter};
+use crate::{dialect::Dialect, writer::FormatWriter};
 use qraft_derive::Bindable;
 
 // max size is 32 bytes
-#[derive(Debug, Default, Clone, Bindable)]
+#[derive(Debug, Clone, Bindable)]
 pub enum Bind {
-    #[default]
-    #[bindable(ignore)]
-    Consumed,
     String(Option<String>),
     StaticString(Option<&'static str>),
     Bool(Option<bool>),
@@ -54,6 +56,126 @@ pub enum Bind {
 
     #[cfg(feature = "json")]
     Json(Option<serde_json::Value>),
+
+    #[bindable(ignore)]
+    Array(Box<ArrayBind>),
+}
+
+/// A postgres array sent as a single bind, other dialects expand it into one
+/// bind per element.
+#[derive(Debug, Clone)]
+pub enum ArrayBind {
+    Bool(Vec<bool>),
+    I16(Vec<i16>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+    String(Vec<String>),
+    #[cfg(feature = "uuid")]
+    Uuid(Vec<uuid::Uuid>),
+}
+
+impl ArrayBind {
+    pub fn len(&self) -> usize {
+        match self {
+            ArrayBind::Bool(values) => values.len(),
+            ArrayBind::I16(values) => values.len(),
+            ArrayBind::I32(values) => values.len(),
+            ArrayBind::I64(values) => values.len(),
+            ArrayBind::F32(values) => values.len(),
+            ArrayBind::F64(values) => values.len(),
+            ArrayBind::String(values) => values.len(),
+            #[cfg(feature = "uuid")]
+            ArrayBind::Uuid(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn elements(&self) -> Binds {
+        fn collect<T: IntoBind + Clone>(values: &[T]) -> Binds {
+            Binds::Many(values.iter().cloned().map(IntoBind::into_bind).collect())
+        }
+        match self {
+            ArrayBind::Bool(values) => collect(values),
+            ArrayBind::I16(values) => collect(values),
+            ArrayBind::I32(values) => collect(values),
+            ArrayBind::I64(values) => collect(values),
+            ArrayBind::F32(values) => collect(values),
+            ArrayBind::F64(values) => collect(values),
+            ArrayBind::String(values) => collect(values),
+            #[cfg(feature = "uuid")]
+            ArrayBind::Uuid(values) => collect(values),
+        }
+    }
+}
+
+pub trait IntoArrayBind {
+    fn into_array_bind(self) -> ArrayBind;
+}
+
+impl IntoArrayBind for ArrayBind {
+    fn into_array_bind(self) -> ArrayBind {
+        self
+    }
+}
+
+macro_rules! impl_into_array_bind {
+    ($variant:ident, $ty:ty) => {
+        impl IntoArrayBind for Vec<$ty> {
+            fn into_array_bind(self) -> ArrayBind {
+                ArrayBind::$variant(self)
+            }
+        }
+
+        impl<const N: usize> IntoArrayBind for [$ty; N] {
+            fn into_array_bind(self) -> ArrayBind {
+                ArrayBind::$variant(Vec::from(self))
+            }
+        }
+    };
+}
+
+impl_into_array_bind!(Bool, bool);
+impl_into_array_bind!(I16, i16);
+impl_into_array_bind!(I32, i32);
+impl_into_array_bind!(I64, i64);
+impl_into_array_bind!(F32, f32);
+impl_into_array_bind!(F64, f64);
+impl_into_array_bind!(String, String);
+#[cfg(feature = "uuid")]
+impl_into_array_bind!(Uuid, uuid::Uuid);
+
+impl IntoArrayBind for Vec<&str> {
+    fn into_array_bind(self) -> ArrayBind {
+        ArrayBind::String(self.into_iter().map(str::to_string).collect())
+    }
+}
+
+impl<const N: usize> IntoArrayBind for [&str; N] {
+    fn into_array_bind(self) -> ArrayBind {
+        ArrayBind::String(self.into_iter().map(str::to_string).collect())
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'q> EncodeBind<'q, sqlx::Postgres> for ArrayBind {
+    fn encode_bind(self, binds: &mut <sqlx::Postgres as sqlx::Database>::Arguments<'q>) {
+        let _ = match self {
+            ArrayBind::Bool(values) => binds.add(values),
+            ArrayBind::I16(values) => binds.add(values),
+            ArrayBind::I32(values) => binds.add(values),
+            ArrayBind::I64(values) => binds.add(values),
+            ArrayBind::F32(values) => binds.add(values),
+            ArrayBind::F64(values) => binds.add(values),
+            ArrayBind::String(values) => binds.add(values),
+            #[cfg(feature = "uuid")]
+            ArrayBind::Uuid(values) => binds.add(values),
+        };
+    }
 }
 
 impl Bind {
@@ -68,30 +190,149 @@ impl Bind {
         bind.into_bind()
     }
 
-    pub fn is_consumed(&self) -> bool {
-        matches!(self, Self::Consumed)
-    }
-
     pub fn new_static_str(value: &'static str) -> Bind {
         Bind::StaticString(Some(value))
     }
+
+    // postgres and sqlite integers are 64 bits signed
+    pub(crate) fn fits(&self, dialect: Dialect) -> bool {
+        match self {
+            Bind::U64(Some(value)) => {
+                matches!(dialect, Dialect::MySql) || i64::try_from(*value).is_ok()
+            }
+            _ => true,
+        }
+    }
 }
 
-#[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+// rendering refuses a u64 past i64::MAX where it can not be stored, when
+// one still reaches an encoder it goes as text instead of wrapping negative
+#[cfg(any(feature = "postgres", feature = "sqlite", feature = "any"))]
+fn signed(value: Option<u64>) -> Result<Option<i64>, Option<String>> {
+    match value.map(i64::try_from) {
+        Some(Ok(value)) => Ok(Some(value)),
+        Some(Err(_)) => Err(value.map(|v| v.to_string())),
+        None => Ok(None),
+    }
+}
+
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
 trait EncodeBind<'q, DB: sqlx::Database> {
     fn encode_bind(self, binds: &mut <DB as sqlx::Database>::Arguments<'q>);
 }
 
 pub type Binds = Array<Bind>;
 
-#[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+#[cfg(feature = "postgres")]
 impl<'q> EncodeBind<'q, sqlx::Postgres> for Bind {
     fn encode_bind(self, binds: &mut <sqlx::Postgres as sqlx::Database>::Arguments<'q>) {
         let _ = match self {
-            Bind::Consumed => {
-                debug_assert!(false, "Can't encode a consumed bind");
+            Bind::String(value) => binds.add(value),
+            Bind::StaticString(value) => binds.add(value),
+            Bind::Bool(value) => binds.add(value),
+            Bind::F32(value) => binds.add(value),
+            Bind::F64(value) => binds.add(value),
+            Bind::I8(value) => binds.add(value),
+            Bind::I16(value) => binds.add(value),
+            Bind::I32(value) => binds.add(value),
+            Bind::I64(value) => binds.add(value),
+            Bind::U8(value) => binds.add(value.map(i16::from)),
+            Bind::U16(value) => binds.add(value.map(i32::from)),
+            Bind::U32(value) => binds.add(value.map(i64::from)),
+            Bind::U64(value) => match signed(value) {
+                Ok(value) => binds.add(value),
+                Err(text) => binds.add(text),
+            },
+            Bind::VecBytes(items) => binds.add(items),
+            #[cfg(feature = "time")]
+            Bind::Date(value) => binds.add(value),
+            #[cfg(feature = "time")]
+            Bind::Time(value) => binds.add(value),
+            #[cfg(feature = "time")]
+            Bind::Timestamptz(value) => binds.add(value),
+            #[cfg(feature = "time")]
+            Bind::Timestamp(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoDate(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTime(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamptzUtc(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamptzLocal(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamp(value) => binds.add(value),
+            #[cfg(feature = "uuid")]
+            Bind::Uuid(value) => binds.add(value),
+            #[cfg(feature = "json")]
+            Bind::Json(value) => binds.add(value),
+            Bind::Array(array) => {
+                array.encode_bind(binds);
+                Ok(())
+            }
+        };
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl<'q> EncodeBind<'q, sqlx::MySql> for Bind {
+    fn encode_bind(self, binds: &mut <sqlx::MySql as sqlx::Database>::Arguments<'q>) {
+        let _ = match self {
+            Bind::String(value) => binds.add(value),
+            Bind::StaticString(value) => binds.add(value),
+            Bind::Bool(value) => binds.add(value),
+            Bind::F32(value) => binds.add(value),
+            Bind::F64(value) => binds.add(value),
+            Bind::I8(value) => binds.add(value),
+            Bind::I16(value) => binds.add(value),
+            Bind::I32(value) => binds.add(value),
+            Bind::I64(value) => binds.add(value),
+            Bind::U8(value) => binds.add(value),
+            Bind::U16(value) => binds.add(value),
+            Bind::U32(value) => binds.add(value),
+            Bind::U64(value) => binds.add(value),
+            Bind::VecBytes(items) => binds.add(items),
+            #[cfg(feature = "time")]
+            Bind::Date(value) => binds.add(value),
+            #[cfg(feature = "time")]
+            Bind::Time(value) => binds.add(value),
+            #[cfg(feature = "time")]
+            Bind::Timestamptz(value) => binds.add(value),
+            #[cfg(feature = "time")]
+            Bind::Timestamp(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoDate(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTime(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamptzUtc(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamptzLocal(value) => binds.add(value),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamp(value) => binds.add(value),
+            #[cfg(feature = "uuid")]
+            Bind::Uuid(value) => binds.add(value),
+            #[cfg(feature = "json")]
+            Bind::Json(value) => binds.add(value),
+            Bind::Array(array) => {
+                for element in array.elements() {
+                    EncodeBind::<sqlx::MySql>::encode_bind(element, binds);
+                }
                 Ok(())
             }
+        };
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<'q> EncodeBind<'q, sqlx::Sqlite> for Bind {
+    fn encode_bind(self, binds: &mut <sqlx::Sqlite as sqlx::Database>::Arguments<'q>) {
+        let _ = match self {
             Bind::String(value) => binds.add(value),
             Bind::StaticString(value) => binds.add(value),
             Bind::Bool(value) => binds.add(value),
@@ -101,10 +342,14 @@ impl<'q> EncodeBind<'q, sqlx::Postgres> for Bind {
             Bind::I16(value) => binds.add(value),
             Bind::I32(value) => binds.add(value),
             Bind::I64(value) => binds.add(value),
-            Bind::U8(value) => binds.add(value.map(|v| v as i8)),
-            Bind::U16(value) => binds.add(value.map(|v| v as i16)),
-            Bind::U32(value) => binds.add(value.map(|v| v as i32)),
-            Bind::U64(value) => binds.add(value.map(|v| v as i64)),
+            Bind::U8(value) => binds.add(value),
+            Bind::U16(value) => binds.add(value),
+            Bind::U32(value) => binds.add(value),
+            // sqlite integers are 64 bits signed
+            Bind::U64(value) => match signed(value) {
+                Ok(value) => binds.add(value),
+                Err(text) => binds.add(text),
+            },
             Bind::VecBytes(items) => binds.add(items),
             #[cfg(feature = "time")]
             Bind::Date(value) => binds.add(value),
@@ -128,11 +373,81 @@ impl<'q> EncodeBind<'q, sqlx::Postgres> for Bind {
             Bind::Uuid(value) => binds.add(value),
             #[cfg(feature = "json")]
             Bind::Json(value) => binds.add(value),
+            Bind::Array(array) => {
+                for element in array.elements() {
+                    EncodeBind::<sqlx::Sqlite>::encode_bind(element, binds);
+                }
+                Ok(())
+            }
         };
     }
 }
 
-#[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+// the any driver only carries the types every backend shares, the others
+// are sent as text and left to the database to cast
+#[cfg(feature = "any")]
+impl<'q> EncodeBind<'q, sqlx::Any> for Bind {
+    fn encode_bind(self, binds: &mut <sqlx::Any as sqlx::Database>::Arguments<'q>) {
+        let _ = match self {
+            Bind::String(value) => binds.add(value),
+            Bind::StaticString(value) => binds.add(value),
+            Bind::Bool(value) => binds.add(value),
+            Bind::F32(value) => binds.add(value),
+            Bind::F64(value) => binds.add(value),
+            Bind::I8(value) => binds.add(value.map(i16::from)),
+            Bind::I16(value) => binds.add(value),
+            Bind::I32(value) => binds.add(value),
+            Bind::I64(value) => binds.add(value),
+            Bind::U8(value) => binds.add(value.map(i16::from)),
+            Bind::U16(value) => binds.add(value.map(i32::from)),
+            Bind::U32(value) => binds.add(value.map(i64::from)),
+            Bind::U64(value) => match signed(value) {
+                Ok(value) => binds.add(value),
+                Err(text) => binds.add(text),
+            },
+            Bind::VecBytes(items) => binds.add(items),
+            #[cfg(feature = "time")]
+            Bind::Date(value) => binds.add(value.map(|v| v.to_string())),
+            #[cfg(feature = "time")]
+            Bind::Time(value) => binds.add(value.map(|v| v.to_string())),
+            #[cfg(feature = "time")]
+            Bind::Timestamptz(value) => binds.add(value.and_then(|v| {
+                v.format(&time::format_description::well_known::Rfc3339)
+                    .ok()
+            })),
+            #[cfg(feature = "time")]
+            Bind::Timestamp(value) => binds.add(value.map(|v| v.to_string())),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoDate(value) => binds.add(value.map(|v| v.to_string())),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTime(value) => binds.add(value.map(|v| v.to_string())),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamptzUtc(value) => binds.add(value.map(|v| v.to_rfc3339())),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamptzLocal(value) => binds.add(value.map(|v| v.to_rfc3339())),
+            #[cfg(feature = "chrono")]
+            Bind::ChronoTimestamp(value) => binds.add(value.map(|v| v.to_string())),
+            #[cfg(feature = "uuid")]
+            Bind::Uuid(value) => binds.add(value.map(|v| v.to_string())),
+            #[cfg(feature = "json")]
+            Bind::Json(value) => binds.add(value.map(|v| v.to_string())),
+            // the any driver has no arrays
+            Bind::Array(array) => {
+                for element in array.elements() {
+                    EncodeBind::<sqlx::Any>::encode_bind(element, binds);
+                }
+                Ok(())
+            }
+        };
+    }
+}
+
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
 impl<'q, DB> IntoArguments<'q, DB> for Binds
 where
     DB: sqlx::Database,
@@ -147,16 +462,6 @@ where
     }
 }
 
-impl TakeBindings for Binds {
-    fn take_bindings(&mut self) -> Binds {
-        let b: Vec<Bind> = self
-            .iter_mut()
-            .map(|v| std::mem::replace(v, Bind::Consumed))
-            .collect();
-        Binds::Many(b)
-    }
-}
-
 impl IntoBinds for Binds {
     fn into_binds(self) -> Binds {
         self
@@ -177,11 +482,11 @@ impl FormatWriter for Binds {
         &self,
         context: &mut crate::writer::FormatContext<'_, W>,
     ) -> std::fmt::Result {
-        for (index, _) in self.iter().enumerate() {
+        for (index, bind) in self.iter().enumerate() {
             if index > 0 {
                 context.writer.write_str(", ")?;
             }
-            context.write_placeholder()?;
+            context.write_bind(bind)?;
         }
         Ok(())
     }
@@ -341,38 +646,6 @@ impl<T> Array<T> {
     }
 }
 
-impl Binds {
-    pub fn normalize(&mut self) {
-        let old = std::mem::take(self);
-
-        *self = match old {
-            Array::None => Array::None,
-
-            Array::One(b) => {
-                if b.is_consumed() {
-                    Array::None
-                } else {
-                    Array::One(b)
-                }
-            }
-
-            Array::Many(mut vec) => {
-                vec.retain(|b| !b.is_consumed());
-
-                match vec.len() {
-                    0 => Array::None,
-                    1 => {
-                        // safe we have at least 1
-                        let only = vec.into_iter().next().expect("we already checked the len");
-                        Array::One(only)
-                    }
-                    _ => Array::Many(vec),
-                }
-            }
-        };
-    }
-}
-
 pub trait IntoBind {
     fn into_bind(self) -> Bind;
 }
@@ -2,13 +2,43 @@ use std::mem;
 
 use qraft_derive::{condition_variant, or_variant, variant};
 
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
+use crate::dialect::HasExecutorDialect;
 use crate::{
-    bind::{Binds, IntoBinds}, col::{
+    Error, Ident, Raw,
+    bind::{Binds, IntoArrayBind, IntoBinds},
+    col::{
         AliasSub, IntoColumns, IntoProjections, IntoTable, ProjectionSchema, Projections,
         TableSchema,
-    }, dialect::{Dialect, HasDialect}, expr::{
-        between::BetweenOperator, binary::Operator, cond::{Conditions, Conjunction}, exists::{ExistsExpr, ExistsOperator}, fncall::{Aggregate, AggregateCall}, r#in::InOperator, list::IntoInList, order::{Order, Ordering}, unary::UnaryOperator, Expr, IntoLhsExpr, IntoOperator, IntoRhsExpr, TakeBindings
-    }, ident::{IntoIdent, TableRef}, insert::{Columns, InsertBuilder}, join::{JoinClause, JoinType, Joins}, raw::IntoRaw, row::Row, writer::{FormatContext, FormatWriter}, Ident, Raw
+    },
+    cte::{CommonTable, WithClause},
+    dialect::{Dialect, HasDialect},
+    expr::{
+        Expr, IntoLhsExpr, IntoOperator, IntoRhsExpr,
+        between::BetweenOperator,
+        binary::Operator,
+        cond::{Conditions, Conjunction},
+        exists::{ExistsExpr, ExistsOperator},
+        fncall::{Aggregate, AggregateCall},
+        r#in::InOperator,
+        list::{InList, IntoInList},
+        order::{Order, Ordering},
+        unary::UnaryOperator,
+        window::{NamedWindow, NamedWindows, Window},
+    },
+    ident::{IntoIdent, TableRef},
+    insert::{Columns, InsertBuilder},
+    join::{JoinClause, JoinType, Joins},
+    lock::{Lock, LockStrength, LockWait},
+    raw::IntoRaw,
+    row::Row,
+    union::{UnionClause, UnionType, Unions, write_operand_close, write_operand_open},
+    writer::{FormatContext, FormatWriter},
 };
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -22,19 +52,28 @@ pub enum QueryKind {
     Update,
 }
 
-impl TakeBindings for Builder {
-    fn take_bindings(&mut self) -> Binds {
-        self.binds.take_bindings()
-    }
+/// How postgres and sqlite find the rows of an update or delete with joins.
+/// Mysql always uses its multi table form.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum MutationStrategy {
+    /// `update ... from` (sqlite 3.33+) and `delete ... using` (postgres)
+    /// when the joins are plain inner joins, `RowId` otherwise.
+    #[default]
+    Native,
+    /// `where ctid in (select ...)` on postgres, `rowid` on sqlite.
+    RowId,
+    /// Same as `RowId` through a key column, for `without rowid` tables and
+    /// engines lacking `ctid`.
+    PrimaryKey(Ident),
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Builder {
     ty: QueryKind,
     distinct: bool,
+    maybe_with: Option<WithClause>,
     maybe_table: Option<TableRef>,
     projections: Projections,
-    binds: Binds,
     maybe_where: Option<Conditions>,
     maybe_having: Option<Conditions>,
     maybe_limit: Option<usize>,
@@ -43,6 +82,12 @@ pub struct Builder {
     maybe_joins: Option<Joins>,
     maybe_group_by: Option<Columns>,
     maybe_sets: Option<Row>,
+    maybe_unions: Option<Unions>,
+    maybe_windows: Option<NamedWindows>,
+    maybe_returning: Option<Columns>,
+    maybe_lock: Option<Lock>,
+    mutation: MutationStrategy,
+    binds: Binds,
 }
 
 pub trait IntoBuilder {
@@ -82,14 +127,36 @@ impl Builder {
         Self::default()
     }
 
+    /// Only the binds added through [`Builder::add_binding`], the others
+    /// are gathered when the query is rendered.
+    #[deprecated(note = "binds are gathered while rendering, use `build_sql` to get them")]
     pub fn bindings(&self) -> &Binds {
         &self.binds
     }
 
+    #[deprecated(note = "binds are gathered while rendering, use `build_sql` to get them")]
     pub fn bindings_mut(&mut self) -> &mut Binds {
         &mut self.binds
     }
 
+    /// Appends binds for placeholders written by hand, they follow the
+    /// binds of the query.
+    #[deprecated(note = "pass the binds to the `*_raw` method writing the placeholders")]
+    pub fn add_binding<B>(&mut self, bind: B) -> &mut Self
+    where
+        B: IntoBinds,
+    {
+        self.binds.append(bind.into_binds());
+        self
+    }
+
+    /// Drops the binds added through [`Builder::add_binding`].
+    #[deprecated(note = "pass the binds to the `*_raw` method writing the placeholders")]
+    pub fn reset_bindings(&mut self) -> &mut Self {
+        self.binds = Binds::None;
+        self
+    }
+
     pub fn inserting(&mut self) -> InsertBuilder {
         // cheap clone o(1)
         let table = self.maybe_table.clone().unwrap_or_default();
@@ -115,13 +182,61 @@ impl Builder {
         F: IntoBuilder,
         I: IntoIdent,
     {
-        let mut inner = inner.into_builder();
-        self.binds.append(inner.take_bindings());
+        let inner = inner.into_builder();
         let aliased = AliasSub::new(inner, alias);
         self.maybe_table = Some(TableRef::AliasSub(aliased));
         self
     }
 
+    // common table expressions
+
+    pub fn with<N, Q>(&mut self, name: N, query: Q) -> &mut Self
+    where
+        N: IntoIdent,
+        Q: IntoBuilder,
+    {
+        let table = CommonTable::new(name.into_ident(), query.into_builder());
+        self.push_common_table(table)
+    }
+
+    pub fn with_materialized<N, Q>(&mut self, name: N, query: Q) -> &mut Self
+    where
+        N: IntoIdent,
+        Q: IntoBuilder,
+    {
+        let table = CommonTable::new(name.into_ident(), query.into_builder()).materialized();
+        self.push_common_table(table)
+    }
+
+    pub fn with_recursive<N, C, A, R>(
+        &mut self,
+        name: N,
+        columns: C,
+        anchor: A,
+        recursive: R,
+    ) -> &mut Self
+    where
+        N: IntoIdent,
+        C: IntoColumns,
+        A: IntoBuilder,
+        R: IntoBuilder,
+    {
+        let table = CommonTable::new(name.into_ident(), anchor.into_builder())
+            .recursive(columns.into_columns(), recursive.into_builder());
+        self.push_common_table(table)
+    }
+
+    pub fn reset_with(&mut self) -> &mut Self {
+        self.maybe_with = None;
+        self
+    }
+
+    fn push_common_table(&mut self, table: CommonTable) -> &mut Self {
+        let with = self.maybe_with.get_or_insert_default();
+        with.push(table);
+        self
+    }
+
     // conditionnals
 
     pub fn when<F>(&mut self, condition: bool, builder: F) -> &mut Self
@@ -223,9 +338,40 @@ impl Builder {
         self
     }
 
+    /// Mysql has no full outer join, it is emulated with a left join union
+    /// all right join when the query only has that one join, the order by
+    /// then uses the output column names. Sqlite only has it since 3.39, it
+    /// is emulated the same way with the left join turned around, a `*`
+    /// projection is spelled out per table. Any other shape is an error on
+    /// both.
+    pub fn full_join<T, C, O, CC>(
+        &mut self,
+        table: T,
+        column: C,
+        operator: O,
+        other_column: CC,
+    ) -> &mut Self
+    where
+        T: IntoTable,
+        C: IntoTable,
+        O: IntoOperator,
+        CC: IntoTable,
+    {
+        self.full_join_clause(table, |join| {
+            join.on(column, operator, other_column);
+        });
+        self
+    }
+
     pub fn cross_join<T: IntoTable>(&mut self, table: T) -> &mut Self {
-        let mut join = JoinClause::new(JoinType::Cross, table.into_table());
-        self.binds.append(join.take_bindings());
+        let join = JoinClause::new(JoinType::Cross, table.into_table());
+        let target = self.maybe_joins.get_or_insert_default();
+        target.push(join);
+        self
+    }
+
+    pub fn natural_join<T: IntoTable>(&mut self, table: T) -> &mut Self {
+        let join = JoinClause::new(JoinType::Natural, table.into_table());
         let target = self.maybe_joins.get_or_insert_default();
         target.push(join);
         self
@@ -237,8 +383,7 @@ impl Builder {
         A: IntoIdent,
         J: FnOnce(&mut JoinClause),
     {
-        let mut inner = sub.into_builder();
-        self.binds.append(inner.take_bindings());
+        let inner = sub.into_builder();
         let aliased = AliasSub::new(inner, alias);
         let table_ref = TableRef::AliasSub(aliased);
         self.join_clause(table_ref, clause);
@@ -251,8 +396,7 @@ impl Builder {
         A: IntoIdent,
         J: FnOnce(&mut JoinClause),
     {
-        let mut inner = sub.into_builder();
-        self.binds.append(inner.take_bindings());
+        let inner = sub.into_builder();
         let aliased = AliasSub::new(inner, alias);
         let table_ref = TableRef::AliasSub(aliased);
         self.left_join_clause(table_ref, clause);
@@ -265,14 +409,43 @@ impl Builder {
         A: IntoIdent,
         J: FnOnce(&mut JoinClause),
     {
-        let mut inner = sub.into_builder();
-        self.binds.append(inner.take_bindings());
+        let inner = sub.into_builder();
         let aliased = AliasSub::new(inner, alias);
         let table_ref = TableRef::AliasSub(aliased);
         self.right_join_clause(table_ref, clause);
         self
     }
 
+    /// Joins a subquery that can reference the previous tables, supported by
+    /// postgres and mysql 8.0.14+. Without conditions it joins `on true`.
+    pub fn join_lateral<F, A, J>(&mut self, sub: F, alias: A, clause: J) -> &mut Self
+    where
+        F: IntoBuilder,
+        A: IntoIdent,
+        J: FnOnce(&mut JoinClause),
+    {
+        let aliased = AliasSub::new(sub.into_builder(), alias);
+        let mut join = JoinClause::new(JoinType::Inner, TableRef::AliasSub(aliased)).lateral();
+        clause(&mut join);
+        let target = self.maybe_joins.get_or_insert_default();
+        target.push(join);
+        self
+    }
+
+    pub fn left_join_lateral<F, A, J>(&mut self, sub: F, alias: A, clause: J) -> &mut Self
+    where
+        F: IntoBuilder,
+        A: IntoIdent,
+        J: FnOnce(&mut JoinClause),
+    {
+        let aliased = AliasSub::new(sub.into_builder(), alias);
+        let mut join = JoinClause::new(JoinType::Left, TableRef::AliasSub(aliased)).lateral();
+        clause(&mut join);
+        let target = self.maybe_joins.get_or_insert_default();
+        target.push(join);
+        self
+    }
+
     pub fn join_clause<T, F>(&mut self, table: T, sub: F) -> &mut Self
     where
         T: IntoTable,
@@ -280,7 +453,6 @@ impl Builder {
     {
         let mut join = JoinClause::new(JoinType::Inner, table.into_table());
         sub(&mut join);
-        self.binds.append(join.take_bindings());
         let target = self.maybe_joins.get_or_insert_default();
         target.push(join);
         self
@@ -293,7 +465,6 @@ impl Builder {
     {
         let mut join = JoinClause::new(JoinType::Left, table.into_table());
         sub(&mut join);
-        self.binds.append(join.take_bindings());
         let target = self.maybe_joins.get_or_insert_default();
         target.push(join);
         self
@@ -306,7 +477,18 @@ impl Builder {
     {
         let mut join = JoinClause::new(JoinType::Right, table.into_table());
         sub(&mut join);
-        self.binds.append(join.take_bindings());
+        let target = self.maybe_joins.get_or_insert_default();
+        target.push(join);
+        self
+    }
+
+    pub fn full_join_clause<T, F>(&mut self, table: T, sub: F) -> &mut Self
+    where
+        T: IntoTable,
+        F: FnOnce(&mut JoinClause),
+    {
+        let mut join = JoinClause::new(JoinType::Full, table.into_table());
+        sub(&mut join);
         let target = self.maybe_joins.get_or_insert_default();
         target.push(join);
         self
@@ -325,6 +507,92 @@ impl Builder {
         self
     }
 
+    // named windows
+
+    pub fn window<N, F>(&mut self, name: N, window: F) -> &mut Self
+    where
+        N: IntoIdent,
+        F: FnOnce(&mut Window),
+    {
+        let mut inner = Window::new();
+        window(&mut inner);
+        let named = NamedWindow::new(name.into_ident(), inner);
+        let target = self.maybe_windows.get_or_insert_default();
+        target.push(named);
+        self
+    }
+
+    pub fn reset_windows(&mut self) -> &mut Self {
+        self.maybe_windows = None;
+        self
+    }
+
+    /// Only used by updates and deletes, rendered on postgres and sqlite.
+    pub fn returning<C: IntoColumns>(&mut self, columns: C) -> &mut Self {
+        self.maybe_returning = Some(columns.into_columns());
+        self
+    }
+
+    /// Picks how a joined update or delete finds its rows, see [`MutationStrategy`].
+    pub fn mutation_strategy(&mut self, strategy: MutationStrategy) -> &mut Self {
+        self.mutation = strategy;
+        self
+    }
+
+    pub fn reset_returning(&mut self) -> &mut Self {
+        self.maybe_returning = None;
+        self
+    }
+
+    // locking, rendered after limit and offset and ignored on sqlite unless
+    // strict, the options only apply once a strength is picked. A select with
+    // unions can not be locked
+
+    pub fn lock_for_update(&mut self) -> &mut Self {
+        let lock = self.maybe_lock.get_or_insert_default();
+        lock.maybe_strength = Some(LockStrength::Update);
+        self
+    }
+
+    pub fn shared_lock(&mut self) -> &mut Self {
+        let lock = self.maybe_lock.get_or_insert_default();
+        lock.maybe_strength = Some(LockStrength::Share);
+        self
+    }
+
+    /// Makes `try_*` and the executors return [`Error::Unsupported`] on
+    /// sqlite, which has no row locks, instead of leaving the lock out.
+    pub fn strict_lock(&mut self) -> &mut Self {
+        let lock = self.maybe_lock.get_or_insert_default();
+        lock.strict = true;
+        self
+    }
+
+    pub fn skip_locked(&mut self) -> &mut Self {
+        let lock = self.maybe_lock.get_or_insert_default();
+        lock.wait = LockWait::SkipLocked;
+        self
+    }
+
+    pub fn no_wait(&mut self) -> &mut Self {
+        let lock = self.maybe_lock.get_or_insert_default();
+        lock.wait = LockWait::NoWait;
+        self
+    }
+
+    /// Restricts the lock to the given tables of a joined select.
+    pub fn of<T: IntoColumns>(&mut self, tables: T) -> &mut Self {
+        let lock = self.maybe_lock.get_or_insert_default();
+        let target = lock.maybe_of.get_or_insert_default();
+        target.append(tables.into_columns());
+        self
+    }
+
+    pub fn reset_lock(&mut self) -> &mut Self {
+        self.maybe_lock = None;
+        self
+    }
+
     // where stuff
 
     pub fn reset_where(&mut self) -> &mut Self {
@@ -333,7 +601,7 @@ impl Builder {
     }
 
     pub fn is_dirty(&self) -> bool {
-        !self.binds.is_empty()
+        self.maybe_with.is_some()
             || self.maybe_where.is_some()
             || self.maybe_having.is_some()
             || self.maybe_order.is_some()
@@ -341,22 +609,12 @@ impl Builder {
             || self.maybe_offset.is_some()
             || self.maybe_joins.is_some()
             || self.maybe_group_by.is_some()
+            || self.maybe_unions.is_some()
+            || self.maybe_windows.is_some()
+            || self.maybe_lock.as_ref().is_some_and(Lock::is_locking)
             || !self.projections.is_empty()
     }
 
-    pub fn add_binding<B>(&mut self, bind: B) -> &mut Self
-    where
-        B: IntoBinds,
-    {
-        self.binds.append(bind.into_binds());
-        self
-    }
-
-    pub fn reset_bindings(&mut self) -> &mut Self {
-        self.binds = Binds::None;
-        self
-    }
-
     #[or_variant]
     pub fn where_clause<C, O, V>(&mut self, column: C, operator: O, value: V) -> &mut Self
     where
@@ -365,14 +623,26 @@ impl Builder {
         V: IntoRhsExpr,
     {
         let conditions = self.maybe_where.get_or_insert_default();
-        let mut lhs = column.into_lhs_expr();
-        let mut rhs = value.into_rhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = column.into_lhs_expr();
+        let rhs = value.into_rhs_expr();
         conditions.push_binary(Conjunction::And, lhs, rhs, operator.into_operator());
         self
     }
 
+    // collects the where conditions written in a closure, for the
+    // expressions embedding a predicate
+    pub(crate) fn where_conditions<F>(sub: F) -> Conditions
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut inner = Self {
+            ty: QueryKind::Where,
+            ..Default::default()
+        };
+        sub(&mut inner);
+        inner.maybe_where.unwrap_or_default()
+    }
+
     #[condition_variant]
     fn where_group<F>(&mut self, sub: F) -> &mut Self
     where
@@ -384,9 +654,7 @@ impl Builder {
         };
         sub(&mut inner);
 
-        let binds = inner.take_bindings();
         if let Some(conds) = inner.maybe_where {
-            self.binds.append(binds);
             let target = self.maybe_where.get_or_insert_default();
             target.push_group(Conjunction::And, conds);
         }
@@ -405,9 +673,7 @@ impl Builder {
         };
         sub(&mut inner);
 
-        let binds = inner.take_bindings();
         if let Some(conds) = inner.maybe_where {
-            self.binds.append(binds);
             let target = self.maybe_where.get_or_insert_default();
             target.push_group(Conjunction::AndNot, conds);
         }
@@ -421,10 +687,8 @@ impl Builder {
         R: IntoRaw,
         B: IntoBinds,
     {
-        let raw = raw.into_raw();
-        let binds = binds.into_binds();
+        let raw = raw.into_raw().bind(binds);
         let target = self.maybe_where.get_or_insert_default();
-        self.binds.append(binds);
         target.push_raw(Conjunction::And, raw);
         self
     }
@@ -435,10 +699,8 @@ impl Builder {
         C: IntoLhsExpr,
         V: IntoRhsExpr,
     {
-        let mut lhs = column.into_lhs_expr();
-        let mut rhs = value.into_rhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = column.into_lhs_expr();
+        let rhs = value.into_rhs_expr();
         let target = self.maybe_where.get_or_insert_default();
         target.push_binary(Conjunction::And, lhs, rhs, Operator::Eq);
         self
@@ -451,10 +713,8 @@ impl Builder {
         O: IntoOperator,
         CC: IntoLhsExpr,
     {
-        let mut lhs = column.into_lhs_expr();
-        let mut rhs = other_column.into_lhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = column.into_lhs_expr();
+        let rhs = other_column.into_lhs_expr();
         let target = self.maybe_where.get_or_insert_default();
         target.push_binary(Conjunction::And, lhs, rhs, operator.into_operator());
         self
@@ -462,8 +722,7 @@ impl Builder {
 
     #[variant(UnaryOperator, Null, null, not_null, true, false)]
     fn unary_expr<C: IntoLhsExpr>(&mut self, column: C) -> &mut Self {
-        let mut column = column.into_lhs_expr();
-        self.binds.append(column.take_bindings());
+        let column = column.into_lhs_expr();
         let target = self.maybe_where.get_or_insert_default();
         target.push_unary(Conjunction::And, column, UnaryOperator::Null);
         self
@@ -476,12 +735,9 @@ impl Builder {
         L: IntoRhsExpr,
         H: IntoRhsExpr,
     {
-        let mut lhs = lhs.into_lhs_expr();
-        let mut low = low.into_rhs_expr();
-        let mut high = high.into_rhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(low.take_bindings());
-        self.binds.append(high.take_bindings());
+        let lhs = lhs.into_lhs_expr();
+        let low = low.into_rhs_expr();
+        let high = high.into_rhs_expr();
         let target = self.maybe_where.get_or_insert_default();
         target.push_between(Conjunction::And, lhs, low, high, BetweenOperator::Between);
         self
@@ -494,12 +750,9 @@ impl Builder {
         L: IntoLhsExpr,
         H: IntoLhsExpr,
     {
-        let mut lhs = lhs.into_lhs_expr();
-        let mut low = low.into_lhs_expr();
-        let mut high = high.into_lhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(low.take_bindings());
-        self.binds.append(high.take_bindings());
+        let lhs = lhs.into_lhs_expr();
+        let low = low.into_lhs_expr();
+        let high = high.into_lhs_expr();
         let target = self.maybe_where.get_or_insert_default();
         target.push_between(Conjunction::And, lhs, low, high, BetweenOperator::Between);
         self
@@ -510,8 +763,7 @@ impl Builder {
     where
         Q: IntoBuilder,
     {
-        let mut inner = sub.into_builder();
-        self.binds.append(inner.take_bindings());
+        let inner = sub.into_builder();
         let target = self.maybe_where.get_or_insert_default();
         target.push_exists(Conjunction::And, inner, ExistsOperator::Exists);
         self
@@ -523,15 +775,43 @@ impl Builder {
         L: IntoLhsExpr,
         R: IntoInList,
     {
-        let mut lhs = lhs.into_lhs_expr();
-        let mut rhs = rhs.into_in_list();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = lhs.into_lhs_expr();
+        let rhs = rhs.into_in_list();
+        let target = self.maybe_where.get_or_insert_default();
+        target.push_in(Conjunction::And, lhs, rhs, InOperator::In);
+        self
+    }
+
+    /// Sends the list as one array bind, `= any($1)` on postgres, so the
+    /// statement does not change with the list length. The other dialects
+    /// and `sqlx::Any` pools expand it like [`Builder::where_in`].
+    #[variant(InOperator, In, in_array In, not_in_array NotIn)]
+    fn in_array_expr<L, A>(&mut self, lhs: L, array: A) -> &mut Self
+    where
+        L: IntoLhsExpr,
+        A: IntoArrayBind,
+    {
+        let lhs = lhs.into_lhs_expr();
+        let rhs = InList::Array(array.into_array_bind());
         let target = self.maybe_where.get_or_insert_default();
         target.push_in(Conjunction::And, lhs, rhs, InOperator::In);
         self
     }
 
+    /// Same as [`Builder::where_in`] but an empty list is an error instead
+    /// of matching no rows.
+    pub fn where_in_strict<L, R>(&mut self, lhs: L, rhs: R) -> Result<&mut Self, Error>
+    where
+        L: IntoLhsExpr,
+        R: IntoInList,
+    {
+        let rhs = rhs.into_in_list();
+        if rhs.is_empty() {
+            return Err(Error::EmptyInList);
+        }
+        Ok(self.where_in(lhs, rhs))
+    }
+
     #[condition_variant]
     pub fn where_all<C, O, V>(&mut self, columns: C, operator: O, rhs: V) -> &mut Self
     where
@@ -583,9 +863,8 @@ impl Builder {
     // havings here
 
     fn reset_having(&mut self) -> &mut Self {
-        // not public for now, needs impl Take binds on all conditions
+        // not public for now
         self.maybe_having = None;
-        self.binds = Binds::None;
         self
     }
 
@@ -597,10 +876,8 @@ impl Builder {
         V: IntoRhsExpr,
     {
         let conditions = self.maybe_having.get_or_insert_default();
-        let mut lhs = column.into_lhs_expr();
-        let mut rhs = value.into_rhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = column.into_lhs_expr();
+        let rhs = value.into_rhs_expr();
         conditions.push_binary(Conjunction::And, lhs, rhs, operator.into_operator());
         self
     }
@@ -617,10 +894,8 @@ impl Builder {
         let closure = |builder: &mut Self| {
             for proj in projections {
                 let conditions = builder.maybe_where.get_or_insert_default();
-                let mut lhs = proj.into_table().into_lhs_expr();
-                let mut rhs = value.clone();
-                builder.binds.append(lhs.take_bindings());
-                builder.binds.append(rhs.take_bindings());
+                let lhs = proj.into_table().into_lhs_expr();
+                let rhs = value.clone();
                 conditions.push_binary(conj, lhs, rhs, operator);
             }
         };
@@ -654,7 +929,10 @@ impl Builder {
     }
 
     pub fn reorder(&mut self) -> &mut Self {
-        self.maybe_order = None;
+        match self.maybe_unions {
+            Some(ref mut unions) => unions.maybe_order = None,
+            None => self.maybe_order = None,
+        }
         self
     }
 
@@ -663,27 +941,33 @@ impl Builder {
         R: IntoRaw,
         B: IntoBinds,
     {
-        let binds = binds.into_binds();
-        self.binds.append(binds);
-        let o = self.maybe_order.get_or_insert_default();
-        let raw = raw.into_raw();
+        let o = self.order_mut();
+        let raw = raw.into_raw().bind(binds);
         o.push_raw(raw);
         self
     }
 
     pub fn order_by_random(&mut self) -> &mut Self {
-        let o = self.maybe_order.get_or_insert_default();
+        let o = self.order_mut();
         o.push_random();
         self
     }
 
     #[inline]
     pub(crate) fn order_by_expr(&mut self, ident: Expr, order: Ordering) -> &mut Self {
-        let o = self.maybe_order.get_or_insert_default();
+        let o = self.order_mut();
         o.push_expr(ident, order);
         self
     }
 
+    // once a set operation is added, the ordering applies to the compound
+    fn order_mut(&mut self) -> &mut Order {
+        match self.maybe_unions {
+            Some(ref mut unions) => unions.maybe_order.get_or_insert_default(),
+            None => self.maybe_order.get_or_insert_default(),
+        }
+    }
+
     // select stuff
 
     pub fn select_raw<T, B>(&mut self, value: T, binds: B) -> &mut Self
@@ -691,9 +975,8 @@ impl Builder {
         T: IntoRaw,
         B: IntoBinds,
     {
-        let raw = value.into_raw();
+        let raw = value.into_raw().bind(binds);
         self.projections = Projections::One(Expr::Ident(TableRef::Raw(raw)));
-        self.binds.append(binds.into_binds());
         self
     }
 
@@ -706,7 +989,8 @@ impl Builder {
     where
         T: IntoProjections,
     {
-        self.projections = cols.into_select_proj();
+        let projections = cols.into_select_proj();
+        self.projections = projections;
         self
     }
 
@@ -725,81 +1009,61 @@ impl Builder {
     }
 
     pub fn select_avg<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Avg, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Avg, table);
         self.select(fncall);
         self
     }
 
     pub fn select_max<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Max, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Max, table);
         self.select(fncall);
         self
     }
 
     pub fn select_sum<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Sum, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Sum, table);
         self.select(fncall);
         self
     }
 
     pub fn select_min<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Min, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Min, table);
         self.select(fncall);
         self
     }
 
     pub fn select_count<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Count, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Count, table);
         self.select(fncall);
         self
     }
 
     pub fn add_select_avg<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Avg, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Avg, table);
         self.add_select(fncall);
         self
     }
 
     pub fn add_select_max<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Avg, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Max, table);
         self.add_select(fncall);
         self
     }
 
     pub fn add_select_sum<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Avg, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Sum, table);
         self.add_select(fncall);
         self
     }
 
     pub fn add_select_min<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Min, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Min, table);
         self.add_select(fncall);
         self
     }
 
     pub fn add_select_count<T: IntoIdent>(&mut self, table: T) -> &mut Self {
-        let ident = table.into_ident();
-        let (table, alias) = ident.split_alias();
-        let fncall = AggregateCall::new(Aggregate::Count, table, alias);
+        let fncall = AggregateCall::of(Aggregate::Count, table);
         self.add_select(fncall);
         self
     }
@@ -821,7 +1085,7 @@ impl Builder {
     }
 
     fn reset_select(&mut self) -> &mut Self {
-        // could be made public but needs to impl take bindings on all conds
+        // could be made public
         self.projections.reset();
         self
     }
@@ -839,32 +1103,73 @@ impl Builder {
     // pagination stuff
 
     pub fn limit(&mut self, limit: usize) -> &mut Self {
-        self.maybe_limit = Some(limit);
+        match self.maybe_unions {
+            Some(ref mut unions) => unions.maybe_limit = Some(limit),
+            None => self.maybe_limit = Some(limit),
+        }
         self
     }
 
     pub fn reset_limit(&mut self) -> &mut Self {
-        self.maybe_limit = None;
+        match self.maybe_unions {
+            Some(ref mut unions) => unions.maybe_limit = None,
+            None => self.maybe_limit = None,
+        }
         self
     }
 
     pub fn offset(&mut self, offset: usize) -> &mut Self {
-        self.maybe_offset = Some(offset);
+        match self.maybe_unions {
+            Some(ref mut unions) => unions.maybe_offset = Some(offset),
+            None => self.maybe_offset = Some(offset),
+        }
         self
     }
 
     pub fn reset_offset(&mut self) -> &mut Self {
-        self.maybe_offset = None;
+        match self.maybe_unions {
+            Some(ref mut unions) => unions.maybe_offset = None,
+            None => self.maybe_offset = None,
+        }
+        self
+    }
+
+    // set operations, intersect and except are rendered as is and need
+    // mysql 8.0.31 or newer, like lateral joins the version is not known here
+
+    #[variant(none, UnionType, Union, union_all, intersect, except)]
+    pub fn union<Q>(&mut self, query: Q) -> &mut Self
+    where
+        Q: IntoBuilder,
+    {
+        let inner = query.into_builder();
+        let target = self.maybe_unions.get_or_insert_default();
+        target.push(UnionClause::new(UnionType::Union, inner));
+        self
+    }
+
+    pub fn reset_unions(&mut self) -> &mut Self {
+        self.maybe_unions = None;
         self
     }
 
+    // a select can be used as is on either side of a set operation when it
+    // has no ordering, pagination or set operations of its own
+    pub(crate) fn is_compound_operand(&self) -> bool {
+        self.maybe_order.as_ref().is_none_or(Order::is_empty)
+            && self.maybe_limit.is_none()
+            && self.maybe_offset.is_none()
+            && self.maybe_unions.is_none()
+            && self.maybe_with.is_none()
+    }
+
     pub fn take(&mut self) -> Self {
         Self {
             ty: mem::take(&mut self.ty),
             distinct: self.distinct,
+            maybe_with: self.maybe_with.take(),
             maybe_table: self.maybe_table.take(),
             projections: self.projections.take(),
-            binds: self.binds.take(),
             maybe_where: self.maybe_where.take(),
             maybe_having: self.maybe_having.take(),
             maybe_limit: self.maybe_limit.take(),
@@ -873,6 +1178,12 @@ impl Builder {
             maybe_joins: self.maybe_joins.take(),
             maybe_group_by: self.maybe_group_by.take(),
             maybe_sets: self.maybe_sets.take(),
+            maybe_unions: self.maybe_unions.take(),
+            maybe_windows: self.maybe_windows.take(),
+            maybe_returning: self.maybe_returning.take(),
+            maybe_lock: self.maybe_lock.take(),
+            mutation: mem::take(&mut self.mutation),
+            binds: self.binds.take(),
         }
         //
     }
@@ -880,9 +1191,9 @@ impl Builder {
     fn reset(&mut self) {
         self.ty = QueryKind::Select;
         self.distinct = false;
+        self.maybe_with = None;
         self.projections = Projections::None;
         self.maybe_table = None;
-        self.binds = Binds::None;
         self.maybe_where = None;
         self.maybe_having = None;
         self.maybe_limit = None;
@@ -890,272 +1201,401 @@ impl Builder {
         self.maybe_order = None;
         self.maybe_joins = None;
         self.maybe_group_by = None;
+        self.maybe_unions = None;
+        self.maybe_windows = None;
+        self.maybe_returning = None;
+        self.maybe_lock = None;
+        self.mutation = MutationStrategy::Native;
+        self.binds = Binds::None;
     }
 
     // building the builder
 
     // add the row impl
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn row<DB, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<<DB as sqlx::Database>::Row, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn row<DB, E>(&self, executor: E) -> Result<<DB as sqlx::Database>::Row, Error>
     where
-        DB: sqlx::Database + HasDialect,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_with::<_, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_with::<_, _>(&sql, bindings)
             .fetch_one(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
     pub async fn maybe_row<DB, E>(
-        &mut self,
+        &self,
         executor: E,
-    ) -> Result<Option<<DB as sqlx::Database>::Row>, sqlx::Error>
+    ) -> Result<Option<<DB as sqlx::Database>::Row>, Error>
     where
-        DB: sqlx::Database + HasDialect,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_with::<_, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_with::<_, _>(&sql, bindings)
             .fetch_optional(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn rows<DB, E>(
-        &mut self,
-        executor: E,
-    ) -> Result<Vec<<DB as sqlx::Database>::Row>, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn rows<DB, E>(&self, executor: E) -> Result<Vec<<DB as sqlx::Database>::Row>, Error>
     where
-        DB: sqlx::Database + HasDialect,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_with::<_, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_with::<_, _>(&sql, bindings)
             .fetch_all(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
     pub async fn execute<DB, E>(
-        &mut self,
+        &self,
         executor: E,
-    ) -> Result<<DB as sqlx::Database>::QueryResult, sqlx::Error>
+    ) -> Result<<DB as sqlx::Database>::QueryResult, Error>
     where
-        DB: sqlx::Database + HasDialect,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
         <DB as sqlx::Database>::QueryResult: crate::dialect::HasRowsAffected,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_with::<_, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_with::<_, _>(&sql, bindings)
             .execute(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn maybe_first<DB, T, E>(mut self, executor: E) -> Result<Option<T>, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn maybe_first<DB, T, E>(&self, executor: E) -> Result<Option<T>, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         T: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_as_with::<_, T, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_as_with::<_, T, _>(&sql, bindings)
             .fetch_optional(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn first<DB, R, E>(mut self, executor: E) -> Result<R, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn first<DB, R, E>(&self, executor: E) -> Result<R, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         R: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_as_with::<_, R, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_as_with::<_, R, _>(&sql, bindings)
             .fetch_one(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn all<DB, T, E>(mut self, executor: E) -> Result<Vec<T>, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn all<DB, T, E>(&self, executor: E) -> Result<Vec<T>, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         T: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_as_with::<_, T, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_as_with::<_, T, _>(&sql, bindings)
             .fetch_all(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn maybe_value<DB, T, E>(mut self, executor: E) -> Result<Option<T>, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn maybe_value<DB, T, E>(&self, executor: E) -> Result<Option<T>, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         (T,): for<'r> sqlx::FromRow<'r, DB::Row>,
         T: Send + Unpin,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_scalar_with::<_, T, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_scalar_with::<_, T, _>(&sql, bindings)
             .fetch_optional(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn value<DB, T, E>(mut self, executor: E) -> Result<T, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn value<DB, T, E>(&self, executor: E) -> Result<T, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         (T,): for<'r> sqlx::FromRow<'r, DB::Row>,
         T: Send + Unpin,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
-        let bindings = self.binds.take_bindings();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_scalar_with::<_, T, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_scalar_with::<_, T, _>(&sql, bindings)
             .fetch_one(executor)
-            .await
+            .await?;
+        Ok(result)
     }
 
-    pub(crate) fn update_query<DB>(&mut self, mut row: Row) -> &mut Self
-    where
-        DB: HasDialect,
-    {
-        let row_binds = row.take_bindings();
-
+    pub(crate) fn update_query(&mut self, dialect: Dialect, mut row: Row) -> &mut Self {
         self.ty = QueryKind::Update;
+        // a defaulted column keeps its current value
+        row.values.retain(|_, expr| !matches!(expr, Expr::Default));
         self.maybe_sets = Some(row);
-        self.binds.append(row_binds);
 
         let is_joined = self.maybe_joins.is_some();
-        if is_joined && matches!(DB::DIALECT, Dialect::Postgres | Dialect::Sqlite) {
-            self.ty = QueryKind::Select;
-            let mut builder = Builder {
-                maybe_table: self.maybe_table.clone(),
-                ..Default::default()
-            };
-            let table_name = self.maybe_table.clone();
-            let alias = table_name.unwrap_or_default();
-            let ident = match DB::DIALECT {
-                Dialect::Postgres => {
-                    Ident::new(smol_str::format_smolstr!("{}.ctid", alias.table_name()))
-                }
-                Dialect::Sqlite => {
-                    Ident::new(smol_str::format_smolstr!("{}.rowid", alias.table_name()))
-                }
-                _ => unreachable!(),
-            };
-            self.select(ident);
-            builder.ty = QueryKind::Update;
-            builder.maybe_sets = self.maybe_sets.take();
-
-            if matches!(DB::DIALECT, Dialect::Postgres) {
-                builder.where_in("ctid", self.take());
-            } else if matches!(DB::DIALECT, Dialect::Sqlite) {
-                builder.where_in("rowid", self.take());
-            }
-            *self = builder;
+        if is_joined
+            && matches!(dialect, Dialect::Postgres | Dialect::Sqlite)
+            && !self.can_mutate_natively()
+        {
+            self.mutate_by_subquery(dialect);
         }
 
         self
     }
 
-    fn delete_query<DB: HasDialect>(&mut self) {
-        let dialect = DB::DIALECT;
-        if matches!(dialect, Dialect::Postgres) {
-            let mut builder = Builder {
-                maybe_table: self.maybe_table.clone(),
-                ..Default::default()
-            };
-            let table_name = self.maybe_table.clone();
-            let alias = table_name.unwrap_or_default();
-            let ident = Ident::new(smol_str::format_smolstr!("{}.ctid", alias.table_name()));
-            self.select(ident);
-            builder.ty = QueryKind::Delete;
-            builder.where_in("ctid", self.take());
-            *self = builder;
-        } else if matches!(dialect, Dialect::MySql) {
-            self.ty = QueryKind::Delete;
-        } else if matches!(dialect, Dialect::Sqlite) {
-            let mut builder = Builder {
-                maybe_table: self.maybe_table.clone(),
-                ..Default::default()
-            };
-            let table_name = self.maybe_table.clone();
-            let alias = table_name.unwrap_or_default();
-            let ident = Ident::new(smol_str::format_smolstr!("{}.rowid", alias.table_name()));
-            self.select(ident);
-            builder.ty = QueryKind::Delete;
-            builder.where_in("rowid", self.take());
-            *self = builder;
+    fn delete_query(&mut self, dialect: Dialect) {
+        self.ty = QueryKind::Delete;
+        let is_native = match dialect {
+            Dialect::MySql => true,
+            Dialect::Postgres => self.can_mutate_natively(),
+            // sqlite has no delete ... using
+            Dialect::Sqlite => self.maybe_joins.is_none() && self.can_mutate_natively(),
+        };
+        if !is_native {
+            self.mutate_by_subquery(dialect);
         }
     }
 
+    // the joins become a from or using list, anything reading the joined
+    // rows as a whole needs the subquery
+    fn can_mutate_natively(&self) -> bool {
+        let is_plain = !self.distinct
+            && self.maybe_group_by.is_none()
+            && self.maybe_having.is_none()
+            && self
+                .maybe_order
+                .as_ref()
+                .is_none_or(|order| order.is_empty())
+            && self.maybe_limit.is_none()
+            && self.maybe_offset.is_none()
+            && self.maybe_unions.is_none()
+            && self.maybe_windows.is_none();
+        let are_inner = self.maybe_joins.iter().flatten().all(|join| {
+            matches!(join.join_type(), JoinType::Inner)
+                && !join.is_lateral()
+                && !join.has_using()
+                && !join.conditions().is_empty()
+        });
+        matches!(self.mutation, MutationStrategy::Native) && is_plain && are_inner
+    }
+
+    fn mutate_by_subquery(&mut self, dialect: Dialect) {
+        let key = match self.mutation {
+            MutationStrategy::PrimaryKey(ref key) => key.clone(),
+            _ if matches!(dialect, Dialect::Postgres) => Ident::new_static("ctid"),
+            _ => Ident::new_static("rowid"),
+        };
+        let mut builder = Builder {
+            ty: self.ty,
+            maybe_table: self.maybe_table.clone(),
+            ..Default::default()
+        };
+        let table_name = self.maybe_table.clone();
+        let alias = table_name.unwrap_or_default();
+        let ident = Ident::new(smol_str::format_smolstr!("{}.{}", alias.table_name(), key));
+        self.ty = QueryKind::Select;
+        self.select(ident);
+        builder.maybe_sets = self.maybe_sets.take();
+        builder.maybe_returning = self.maybe_returning.take();
+        builder.where_in(key, self.take());
+        *self = builder;
+    }
+
     // delete query
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn delete<DB, E>(mut self, executor: E) -> Result<bool, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn delete<DB, E>(mut self, executor: E) -> Result<bool, Error>
     where
-        DB: sqlx::Database + HasDialect,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
         <DB as sqlx::Database>::QueryResult: crate::dialect::HasRowsAffected,
     {
         use crate::dialect::HasRowsAffected;
 
-        self.delete_query::<DB>();
+        self.delete_query(executor.dialect()?);
         let value = self.execute::<DB, E>(executor).await?;
         let rows = value.rows_affected();
         Ok(rows > 0)
     }
 
     // update query
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn update<DB, E, R>(mut self, executor: E, row: R) -> Result<bool, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn update<DB, E, R>(mut self, executor: E, row: R) -> Result<bool, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         R: crate::row::IntoRow,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
         <DB as sqlx::Database>::QueryResult: crate::dialect::HasRowsAffected,
     {
         use crate::dialect::HasRowsAffected;
 
-        self.update_query::<DB>(row.into_row());
+        self.update_query(executor.dialect()?, row.into_row());
         let value = self.execute::<DB, E>(executor).await?;
         let rows = value.rows_affected();
         Ok(rows > 0)
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn exists<DB, E>(mut self, executor: E) -> Result<bool, sqlx::Error>
+    /// Deletes and fetches the removed rows, defaults to `returning *`.
+    /// Not available on mysql.
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn delete_returning_all<DB, T, E>(mut self, executor: E) -> Result<Vec<T>, Error>
+    where
+        DB: sqlx::Database,
+        T: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
+        Binds: for<'c> sqlx::IntoArguments<'c, DB>,
+    {
+        let dialect = executor.dialect()?;
+        if matches!(dialect, Dialect::MySql) {
+            return Err(returning_unsupported());
+        }
+
+        if self.maybe_returning.is_none() {
+            self.returning("*");
+        }
+        self.delete_query(dialect);
+        self.all(executor).await
+    }
+
+    /// Updates and fetches the changed rows, defaults to `returning *`.
+    /// Not available on mysql.
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn update_returning_all<DB, T, E, R>(
+        mut self,
+        executor: E,
+        row: R,
+    ) -> Result<Vec<T>, Error>
+    where
+        DB: sqlx::Database,
+        T: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
+        R: crate::row::IntoRow,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
+        Binds: for<'c> sqlx::IntoArguments<'c, DB>,
+    {
+        let dialect = executor.dialect()?;
+        if matches!(dialect, Dialect::MySql) {
+            return Err(returning_unsupported());
+        }
+
+        if self.maybe_returning.is_none() {
+            self.returning("*");
+        }
+        self.update_query(dialect, row.into_row());
+        self.all(executor).await
+    }
+
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn exists<DB, E>(mut self, executor: E) -> Result<bool, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         (bool,): for<'r> sqlx::FromRow<'r, DB::Row>,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
         let self_builder = self.take();
@@ -1170,12 +1610,17 @@ impl Builder {
         builder.value(executor).await
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
-    pub async fn not_exists<DB, E>(mut self, executor: E) -> Result<bool, sqlx::Error>
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn not_exists<DB, E>(mut self, executor: E) -> Result<bool, Error>
     where
-        DB: sqlx::Database + HasDialect,
+        DB: sqlx::Database,
         (bool,): for<'r> sqlx::FromRow<'r, DB::Row>,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
     {
         use crate::{Ident, expr::exists::ExistsExpr};
@@ -1191,26 +1636,392 @@ impl Builder {
         builder.value(executor).await
     }
 
-    pub fn to_sql<Database: HasDialect>(&mut self) -> String {
+    /// Renders the sql alone, binds are left as placeholders.
+    ///
+    /// # Panics
+    ///
+    /// Past the bind limit of the dialect, [`Builder::try_to_sql`] returns
+    /// [`Error::TooManyBinds`] instead.
+    pub fn to_sql<Database: HasDialect>(&self) -> String {
+        self.to_sql_for(Database::DIALECT)
+    }
+
+    /// Renders the query for a dialect only known at runtime.
+    pub fn to_sql_for(&self, dialect: Dialect) -> String {
         let size_hint = 64;
         let mut str = String::with_capacity(size_hint);
-        let mut context = FormatContext::new(&mut str, Database::DIALECT);
-        self.format_writer(&mut context)
-            .expect("should not fail on a string writer");
+        let mut context = FormatContext::new(&mut str, dialect);
+        if let Err(err) = self.format_writer(&mut context) {
+            panic!("{}", context.render_error(err));
+        }
         str
     }
-}
 
-impl FormatWriter for Builder {
-    fn format_writer<W: std::fmt::Write>(
-        &self,
-        context: &mut crate::writer::FormatContext<'_, W>,
-    ) -> std::fmt::Result {
-        // check if we are building a select or delete
+    /// Renders the query along with its bindings, gathered in placeholder
+    /// order. The builder is left untouched so it can be executed again.
+    ///
+    /// # Panics
+    ///
+    /// Past the bind limit of the dialect or on a `u64` bind above `i64::MAX`
+    /// on postgres and sqlite, [`Builder::try_build_sql`] returns
+    /// [`Error::TooManyBinds`] or [`Error::BindOutOfRange`] instead.
+    pub fn build_sql<Database: HasDialect>(&self) -> (String, Binds) {
+        self.build_sql_for(Database::DIALECT)
+    }
 
-        if self.ty == QueryKind::Delete {
-            context.writer.write_str("delete")?;
-        }
+    pub fn build_sql_for(&self, dialect: Dialect) -> (String, Binds) {
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::with_binds(&mut str, dialect);
+        if let Err(err) = self.format_writer(&mut context) {
+            panic!("{}", context.render_error(err));
+        }
+        let binds = context.take_binds();
+        (str, binds)
+    }
+
+    /// Like [`Builder::to_sql`] but validates the query first, an update
+    /// without sets or a join the dialect lacks is an error instead of
+    /// broken sql.
+    pub fn try_to_sql<Database: HasDialect>(&self) -> Result<String, Error> {
+        self.try_to_sql_for(Database::DIALECT)
+    }
+
+    pub fn try_to_sql_for(&self, dialect: Dialect) -> Result<String, Error> {
+        self.validate(dialect)?;
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::new(&mut str, dialect);
+        self.format_writer(&mut context)
+            .map_err(|err| context.render_error(err))?;
+        Ok(str)
+    }
+
+    pub fn try_build_sql<Database: HasDialect>(&self) -> Result<(String, Binds), Error> {
+        self.try_build_sql_for(Database::DIALECT)
+    }
+
+    pub fn try_build_sql_for(&self, dialect: Dialect) -> Result<(String, Binds), Error> {
+        self.try_build_sql_with(dialect, false)
+    }
+
+    // the any driver has no arrays, they go one placeholder per element
+    pub(crate) fn try_build_sql_with(
+        &self,
+        dialect: Dialect,
+        expand_arrays: bool,
+    ) -> Result<(String, Binds), Error> {
+        self.validate(dialect)?;
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::with_binds(&mut str, dialect);
+        context.expand_arrays = expand_arrays;
+        self.format_writer(&mut context)
+            .map_err(|err| context.render_error(err))?;
+        let binds = context.take_binds();
+        Ok((str, binds))
+    }
+
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        if let Some(reason) = self.unsupported_join(dialect) {
+            return Err(Error::Unsupported { dialect, reason });
+        }
+        if let Some(ref lock) = self.maybe_lock
+            && lock.strict
+            && lock.is_locking()
+            && matches!(self.ty, QueryKind::Select)
+            && matches!(dialect, Dialect::Sqlite)
+        {
+            return Err(Error::Unsupported {
+                dialect,
+                reason: "sqlite has no row locks",
+            });
+        }
+        // postgres refuses for update next to a set operation, sqlite leaves
+        // the lock out
+        if let Some(ref unions) = self.maybe_unions
+            && !unions.is_empty()
+            && !matches!(dialect, Dialect::Sqlite)
+            && std::iter::once(self)
+                .chain(unions.queries())
+                .any(|query| query.maybe_lock.as_ref().is_some_and(Lock::is_locking))
+        {
+            return Err(Error::Unsupported {
+                dialect,
+                reason: "row locks can not be taken on a union",
+            });
+        }
+        if matches!(self.ty, QueryKind::Update)
+            && self
+                .maybe_sets
+                .as_ref()
+                .is_none_or(|row| row.values.is_empty())
+        {
+            return Err(Error::EmptyUpdate);
+        }
+        for conditions in [&self.maybe_where, &self.maybe_having]
+            .into_iter()
+            .flatten()
+        {
+            conditions.validate(dialect)?;
+        }
+        // every nested query is rendered in the same dialect
+        let tables = self.maybe_table.iter().chain(
+            self.maybe_joins
+                .iter()
+                .flatten()
+                .filter_map(|join| join.table()),
+        );
+        for table in tables {
+            if let TableRef::AliasSub(sub) = table {
+                sub.inner.validate(dialect)?;
+            }
+        }
+        for join in self.maybe_joins.iter().flatten() {
+            join.conditions().validate(dialect)?;
+        }
+        let nested = self
+            .maybe_with
+            .iter()
+            .flat_map(|with| with.queries())
+            .chain(self.maybe_unions.iter().flat_map(|unions| unions.queries()));
+        for query in nested {
+            query.validate(dialect)?;
+        }
+        let sets = self.maybe_sets.iter().flat_map(|row| row.values.values());
+        for expr in self.projections.iter().chain(sets) {
+            expr.validate(dialect)?;
+        }
+        Ok(())
+    }
+}
+
+impl Builder {
+    fn format_statement<W: std::fmt::Write>(
+        &self,
+        context: &mut crate::writer::FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        if let Some(ref with) = self.maybe_with
+            && !with.is_empty()
+        {
+            with.format_writer(context)?;
+            context.writer.write_char(' ')?;
+        }
+
+        if matches!(context.dialect, Dialect::MySql | Dialect::Sqlite)
+            && self.can_emulate_full_join(context.dialect)
+        {
+            return self.format_full_join_emulation(context);
+        }
+
+        match self.maybe_unions {
+            Some(ref unions) if !unions.is_empty() => {
+                let wrap = self.maybe_order.as_ref().is_some_and(|o| !o.is_empty())
+                    || self.maybe_limit.is_some()
+                    || self.maybe_offset.is_some();
+                if wrap {
+                    write_operand_open(context)?;
+                }
+                self.format_query(context)?;
+                if wrap {
+                    write_operand_close(context)?;
+                }
+                unions.format_writer(context)
+            }
+            _ => self.format_query(context),
+        }
+    }
+}
+
+impl FormatWriter for Builder {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut crate::writer::FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        self.format_statement(context)?;
+        // binds added by hand follow the ones of the query
+        context.push_binds(&self.binds)
+    }
+}
+
+impl Builder {
+    fn has_full_join(&self) -> bool {
+        self.maybe_joins
+            .iter()
+            .flatten()
+            .any(|join| matches!(join.join_type(), JoinType::Full))
+    }
+
+    // the emulation needs to know which rows of the left side matched, it is
+    // only done for a plain select with the full join as its only join
+    fn can_emulate_full_join(&self, dialect: Dialect) -> bool {
+        let Some([join]) = self.maybe_joins.as_deref() else {
+            return false;
+        };
+        // sqlite turns the join around, `*` is spelled out per table to keep
+        // the columns in place
+        let star_in_place = !matches!(dialect, Dialect::Sqlite)
+            || !self.projections.is_empty()
+            || [self.maybe_table.as_ref(), join.table()]
+                .into_iter()
+                .all(|table| matches!(table, Some(TableRef::Ident(_) | TableRef::AliasSub(_))));
+        matches!(join.join_type(), JoinType::Full)
+            && !join.has_using()
+            && !join.conditions().is_empty()
+            && matches!(self.ty, QueryKind::Select)
+            && self.maybe_table.is_some()
+            && self.maybe_group_by.is_none()
+            && self.maybe_having.as_ref().is_none_or(|h| h.is_empty())
+            && self.maybe_unions.as_ref().is_none_or(|u| u.is_empty())
+            && self.maybe_windows.is_none()
+            && self
+                .maybe_lock
+                .as_ref()
+                .is_none_or(|lock| !lock.is_locking())
+            && self
+                .maybe_order
+                .as_ref()
+                .is_none_or(|order| order.by_output_names().is_some())
+            && star_in_place
+    }
+
+    /// Returns why the query can not be rendered for the dialect.
+    fn unsupported_join(&self, dialect: Dialect) -> Option<&'static str> {
+        let joins = self.maybe_joins.iter().flatten();
+        if matches!(dialect, Dialect::Sqlite) && joins.clone().any(JoinClause::is_lateral) {
+            return Some("sqlite does not support lateral joins");
+        }
+        if !self.has_full_join() || self.can_emulate_full_join(dialect) {
+            return None;
+        }
+        // the sqlite version is not known here, the full join is emulated
+        // rather than relying on 3.39
+        match dialect {
+            Dialect::MySql => Some(
+                "mysql has no full outer join, it can only be emulated for a select with a single full join on conditions, ordered by plain columns, without group by, having, unions, windows or locks",
+            ),
+            Dialect::Sqlite => Some(
+                "sqlite only has full outer joins since 3.39, they are emulated for a select with a single full join on conditions between named tables, ordered by plain columns, without group by, having, unions, windows or locks",
+            ),
+            Dialect::Postgres => None,
+        }
+    }
+
+    // select .. left join .. union all select .. right join .. where not exists (
+    // select 1 from <left table> where <on conditions>), sqlite before 3.39
+    // has no right join and gets the left join the other way around
+    fn format_full_join_emulation<W: std::fmt::Write>(
+        &self,
+        context: &mut crate::writer::FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        let Some([join]) = self.maybe_joins.as_deref() else {
+            return self.format_query(context);
+        };
+        let left = Builder {
+            maybe_with: None,
+            maybe_order: None,
+            maybe_limit: None,
+            maybe_offset: None,
+            maybe_joins: Some(vec![join.with_join_type(JoinType::Left)]),
+            ..self.clone()
+        };
+        let mut right = match (context.dialect, join.table()) {
+            (Dialect::Sqlite, Some(table)) => {
+                let mut projections = left.projections.clone();
+                if projections.is_empty() {
+                    for table in [left.maybe_table.as_ref(), Some(table)]
+                        .into_iter()
+                        .flatten()
+                    {
+                        let star =
+                            Ident::new(smol_str::format_smolstr!("{}.*", table.table_name()));
+                        projections.push(Expr::Ident(TableRef::Ident(star)));
+                    }
+                }
+                let swapped = left.maybe_table.clone().unwrap_or_default();
+                Builder {
+                    maybe_table: Some(table.clone()),
+                    maybe_joins: Some(vec![join.with_table(JoinType::Left, swapped)]),
+                    projections,
+                    ..left.clone()
+                }
+            }
+            _ => Builder {
+                maybe_joins: Some(vec![join.with_join_type(JoinType::Right)]),
+                ..left.clone()
+            },
+        };
+
+        let mut matched = Builder {
+            maybe_table: self.maybe_table.clone(),
+            maybe_where: Some(join.conditions().clone()),
+            ..Default::default()
+        };
+        matched.select_one();
+        let mut conditions = Conditions::default();
+        if let Some(w) = right.maybe_where.take()
+            && !w.is_empty()
+        {
+            conditions.push_group(Conjunction::And, w);
+        }
+        conditions.push_exists(Conjunction::And, matched, ExistsOperator::NotExists);
+        right.maybe_where = Some(conditions);
+
+        left.format_query(context)?;
+        // both halves keep their duplicates unless the query asked otherwise
+        if self.distinct {
+            context.writer.write_str(" union ")?;
+        } else {
+            context.writer.write_str(" union all ")?;
+        }
+        right.format_query(context)?;
+
+        // mysql rejects table names in the order of a union
+        if let Some(order) = self.maybe_order.as_ref().and_then(Order::by_output_names)
+            && !order.is_empty()
+        {
+            context.writer.write_str(" order by ")?;
+            order.format_writer(context)?;
+        }
+        if let Some(limit) = self.maybe_limit {
+            write!(context.writer, " limit {}", limit)?;
+        }
+        if let Some(offset) = self.maybe_offset {
+            write!(context.writer, " offset {}", offset)?;
+        }
+        Ok(())
+    }
+
+    fn format_join_sources<W: std::fmt::Write>(
+        &self,
+        keyword: &str,
+        context: &mut crate::writer::FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        let tables = self
+            .maybe_joins
+            .iter()
+            .flatten()
+            .filter_map(|join| join.table());
+        for (index, table) in tables.enumerate() {
+            if index > 0 {
+                context.writer.write_str(", ")?;
+            } else {
+                context.writer.write_str(keyword)?;
+            }
+            table.format_writer(context)?;
+        }
+        Ok(())
+    }
+
+    fn format_query<W: std::fmt::Write>(
+        &self,
+        context: &mut crate::writer::FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        // check if we are building a select or delete
+
+        if self.ty == QueryKind::Delete {
+            context.writer.write_str("delete")?;
+        }
 
         if self.ty == QueryKind::Select {
             if self.distinct {
@@ -1238,30 +2049,62 @@ impl FormatWriter for Builder {
             }
         }
 
-        // joins here
-        let update_cond = matches!(self.ty, QueryKind::Update)
+        // postgres and sqlite list the joined tables in a from or using
+        // clause, their conditions move to the where
+        let joins_as_sources = matches!(self.ty, QueryKind::Update | QueryKind::Delete)
             && matches!(context.dialect, Dialect::Sqlite | Dialect::Postgres);
 
-        if !update_cond {
-            if let Some(ref joins) = self.maybe_joins {
-                context.writer.write_char(' ')?;
-                for (index, join) in joins.iter().enumerate() {
-                    if index > 0 {
-                        context.writer.write_char(' ')?;
-                    }
-                    join.format_writer(context)?;
+        if joins_as_sources && self.ty == QueryKind::Delete {
+            self.format_join_sources(" using ", context)?;
+        }
+
+        if !joins_as_sources && let Some(ref joins) = self.maybe_joins {
+            context.writer.write_char(' ')?;
+            for (index, join) in joins.iter().enumerate() {
+                if index > 0 {
+                    context.writer.write_char(' ')?;
                 }
+                join.format_writer(context)?;
             }
         }
 
-        if let Some(ref row) = self.maybe_sets {
-            if matches!(self.ty, QueryKind::Update) {
-                context.writer.write_str(" set ")?;
-                row.format_assignments(context)?;
-            }
+        if let Some(ref row) = self.maybe_sets
+            && matches!(self.ty, QueryKind::Update)
+        {
+            context.writer.write_str(" set ")?;
+            row.format_assignments(context)?;
         }
 
-        if let Some(ref w) = self.maybe_where {
+        if joins_as_sources && self.ty == QueryKind::Update {
+            self.format_join_sources(" from ", context)?;
+        }
+
+        let join_conditions: Vec<&Conditions> = match self.maybe_joins {
+            Some(ref joins) if joins_as_sources => joins
+                .iter()
+                .map(|join| join.conditions())
+                .filter(|conditions| !conditions.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        };
+        if !join_conditions.is_empty() {
+            let user_where = self.maybe_where.iter().filter(|w| !w.is_empty());
+            for (index, conditions) in join_conditions.into_iter().chain(user_where).enumerate() {
+                if index > 0 {
+                    context.writer.write_str(" and ")?;
+                } else {
+                    context.writer.write_str(" where ")?;
+                }
+                // keeps an `or` from escaping into the other conditions
+                if conditions.needs_group() {
+                    context.writer.write_char('(')?;
+                    conditions.format_writer(context)?;
+                    context.writer.write_char(')')?;
+                } else {
+                    conditions.format_writer(context)?;
+                }
+            }
+        } else if let Some(ref w) = self.maybe_where {
             // if we are not in a where group
             if !w.is_empty() {
                 if matches!(
@@ -1295,13 +2138,25 @@ impl FormatWriter for Builder {
                 }
             }
 
-            if let Some(ref order) = self.maybe_order {
-                if !order.is_empty() {
-                    context.writer.write_str(" order by ")?;
-                    order.format_writer(context)?;
+            if let Some(ref windows) = self.maybe_windows
+                && matches!(self.ty, QueryKind::Select)
+            {
+                context.writer.write_str(" window ")?;
+                for (index, window) in windows.iter().enumerate() {
+                    if index > 0 {
+                        context.writer.write_str(", ")?;
+                    }
+                    window.format_writer(context)?;
                 }
             }
 
+            if let Some(ref order) = self.maybe_order
+                && !order.is_empty()
+            {
+                context.writer.write_str(" order by ")?;
+                order.format_writer(context)?;
+            }
+
             if let Some(limit) = self.maybe_limit {
                 write!(context.writer, " limit {}", limit)?;
             }
@@ -1309,17 +2164,43 @@ impl FormatWriter for Builder {
             if let Some(offset) = self.maybe_offset {
                 write!(context.writer, " offset {}", offset)?;
             }
+
+            if let Some(ref lock) = self.maybe_lock
+                && matches!(self.ty, QueryKind::Select)
+            {
+                lock.format_writer(context)?;
+            }
+        }
+
+        if let Some(ref returning) = self.maybe_returning
+            && matches!(self.ty, QueryKind::Update | QueryKind::Delete)
+            && matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite)
+        {
+            context.writer.write_str(" returning ")?;
+            returning.format_writer(context)?;
         }
 
         Ok(())
     }
 }
 
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
+pub(crate) fn returning_unsupported() -> Error {
+    Error::Unsupported {
+        dialect: Dialect::MySql,
+        reason: "mysql does not support returning",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         bind::{self, Bind},
-        col::ProjectionSchema,
         column_static,
         dialect::{MySql, Postgres, Sqlite},
         raw,
@@ -1352,24 +2233,22 @@ mod tests {
     }
 
     #[allow(dead_code)]
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, crate::Table)]
+    #[qraft(table = "users")]
     struct User {
         id: i64,
         admin: bool,
     }
 
-    // generated ?
-    impl TableSchema for User {
-        fn table() -> Ident {
-            Ident::new_static("users")
-        }
-    }
-
-    // generated ?
-    impl ProjectionSchema for User {
-        fn projections() -> Projections {
-            [column_static("id"), column_static("admin")].into_select_proj()
-        }
+    #[allow(dead_code)]
+    #[derive(crate::Table)]
+    #[qraft(rename_all = "camelCase")]
+    struct TeamMember {
+        member_id: i64,
+        #[qraft(rename = "team")]
+        team_id: i64,
+        #[qraft(skip)]
+        loaded: bool,
     }
 
     #[test]
@@ -1388,6 +2267,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_derive_table() {
+        let mut builder = Builder::table_as::<TeamMember>();
+        builder.select_as::<TeamMember>();
+        assert_eq!(
+            r#"select "memberId", "team" from "team_member""#,
+            builder.to_sql::<Postgres>()
+        );
+    }
+
     #[test]
     fn test_select_raw() {
         let mut builder = Builder::table("users");
@@ -1406,8 +2295,8 @@ mod tests {
             "select price + $1 as fee from \"users\"",
             builder.to_sql::<Postgres>()
         );
-        assert_eq!(1, builder.binds.len());
-        let value = match builder.binds {
+        assert_eq!(1, builder.build_sql::<Postgres>().1.len());
+        let value = match builder.build_sql::<Postgres>().1 {
             bind::Array::None => panic!("should have one value"),
             bind::Array::One(value) => value,
             bind::Array::Many(_) => panic!("wrong size"),
@@ -1566,7 +2455,7 @@ mod tests {
         let mut builder = Builder::table("users");
         builder.limit(42);
         builder.limit(1);
-        assert!(builder.binds.is_empty());
+        assert!(builder.build_sql::<Postgres>().1.is_empty());
         assert_eq!(
             "select * from \"users\" limit 1",
             builder.to_sql::<Postgres>()
@@ -1577,7 +2466,7 @@ mod tests {
     fn test_offset_clause() {
         let mut builder = Builder::table("users");
         builder.offset(42);
-        assert!(builder.binds.is_empty());
+        assert!(builder.build_sql::<Postgres>().1.is_empty());
         assert_eq!(
             "select * from \"users\" offset 42",
             builder.to_sql::<Postgres>()
@@ -1596,7 +2485,7 @@ mod tests {
 
         builder.order_by_desc("username");
 
-        assert!(builder.binds.is_empty());
+        assert!(builder.build_sql::<Postgres>().1.is_empty());
         assert_eq!(
             "select * from \"users\" order by \"id\" asc, \"username\" desc",
             builder.to_sql::<Postgres>()
@@ -1610,13 +2499,13 @@ mod tests {
             .where_eq("id", 1)
             .where_any(["id", "foo", "bar"], Operator::Eq, "baz");
 
-        assert_eq!(builder.binds.len(), 4);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 4);
         assert_eq!(
             "select * from \"users\" where \"id\" = $1 and (\"id\" = $2 or \"foo\" = $3 or \"bar\" = $4)",
             builder.to_sql::<Postgres>()
         );
 
-        assert_eq!(builder.binds.len(), 4);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 4);
 
         builder.reset_where();
 
@@ -1624,7 +2513,7 @@ mod tests {
             .where_eq("id", 1)
             .or_where_any(["id", "foo", "bar"], Operator::Eq, "baz");
 
-        assert_eq!(builder.binds.len(), 4);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 4);
         assert_eq!(
             "select * from \"users\" where \"id\" = $1 or (\"id\" = $2 or \"foo\" = $3 or \"bar\" = $4)",
             builder.to_sql::<Postgres>()
@@ -1642,7 +2531,7 @@ mod tests {
             "select * from \"users\" where \"id\" = $1 and (\"id\" = $2 and \"foo\" = $3 and \"bar\" = $4)",
             builder.to_sql::<Postgres>()
         );
-        assert_eq!(builder.binds.len(), 4);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 4);
         builder.reset_where();
 
         builder
@@ -1653,7 +2542,7 @@ mod tests {
             "select * from \"users\" where \"id\" = $1 or (\"id\" = $2 and \"foo\" = $3 and \"bar\" = $4)",
             builder.to_sql::<Postgres>()
         );
-        assert_eq!(builder.binds.len(), 4);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 4);
     }
 
     #[test]
@@ -1667,7 +2556,7 @@ mod tests {
             "select * from \"users\" where \"id\" = $1 and not (\"id\" = $2 or \"foo\" = $3 or \"bar\" = $4)",
             builder.to_sql::<Postgres>()
         );
-        assert_eq!(builder.binds.len(), 4);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 4);
         builder.reset_where();
 
         builder
@@ -1678,7 +2567,7 @@ mod tests {
             "select * from \"users\" where \"id\" = $1 or not (\"id\" = $2 or \"foo\" = $3 or \"bar\" = $4)",
             builder.to_sql::<Postgres>()
         );
-        assert_eq!(builder.binds.len(), 4);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 4);
     }
 
     #[test]
@@ -1700,7 +2589,297 @@ mod tests {
         });
         let result = r#"select * from "users" inner join "orders" on "foo" = $1"#;
         assert_eq!(result, builder.to_sql::<Postgres>());
-        assert_eq!(builder.binds.len(), 1);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 1);
+    }
+
+    #[test]
+    fn test_full_natural_lateral_join() {
+        let mut builder = Builder::table("users as u");
+        builder
+            .select(["u.name", "p.title"])
+            .full_join("posts as p", "u.id", "=", "p.user_id")
+            .where_eq("p.draft", false)
+            .order_by_asc("name")
+            .limit(10);
+        assert_eq!(
+            r#"select "u"."name", "p"."title" from "users" as "u" full outer join "posts" as "p" on "u"."id" = "p"."user_id" where "p"."draft" = $1 order by "name" asc limit 10"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            "select `u`.`name`, `p`.`title` from `users` as `u` left join `posts` as `p` on `u`.`id` = `p`.`user_id` where `p`.`draft` = ? union all select `u`.`name`, `p`.`title` from `users` as `u` right join `posts` as `p` on `u`.`id` = `p`.`user_id` where (`p`.`draft` = ?) and not exists (select 1 from `users` as `u` where `u`.`id` = `p`.`user_id`) order by `name` asc limit 10",
+            builder.to_sql::<MySql>()
+        );
+        assert_eq!(builder.build_sql::<MySql>().1.len(), 2);
+        // sqlite before 3.39 has no right join either, the left join is
+        // turned around
+        assert_eq!(
+            r#"select "u"."name", "p"."title" from "users" as "u" left join "posts" as "p" on "u"."id" = "p"."user_id" where "p"."draft" = ?1 union all select "u"."name", "p"."title" from "posts" as "p" left join "users" as "u" on "u"."id" = "p"."user_id" where ("p"."draft" = ?2) and not exists (select 1 from "users" as "u" where "u"."id" = "p"."user_id") order by "name" asc limit 10"#,
+            builder.try_to_sql::<Sqlite>().unwrap()
+        );
+        let mut builder = Builder::table("users as u");
+        builder.full_join("posts", "u.id", "=", "posts.user_id");
+        assert_eq!(
+            r#"select * from "users" as "u" left join "posts" on "u"."id" = "posts"."user_id" union all select "u".*, "posts".* from "posts" left join "users" as "u" on "u"."id" = "posts"."user_id" where not exists (select 1 from "users" as "u" where "u"."id" = "posts"."user_id")"#,
+            builder.try_to_sql::<Sqlite>().unwrap()
+        );
+        builder.from(raw("users as u"));
+        assert!(matches!(
+            builder.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        // the order of the union only sees the output columns
+        let mut builder = Builder::table("users as u");
+        builder
+            .select(["u.name", "p.title"])
+            .full_join("posts as p", "u.id", "=", "p.user_id")
+            .order_by_asc("u.name");
+        assert!(
+            builder
+                .try_to_sql::<MySql>()
+                .unwrap()
+                .ends_with("where not exists (select 1 from `users` as `u` where `u`.`id` = `p`.`user_id`) order by `name` asc")
+        );
+        builder.reorder().order_by_asc(crate::lower("u.name"));
+        assert!(matches!(
+            builder.try_to_sql::<MySql>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let mut builder = Builder::table("users");
+        builder.natural_join("profiles");
+        assert_eq!(
+            r#"select * from "users" natural join "profiles""#,
+            builder.to_sql::<Postgres>()
+        );
+
+        let mut builder = Builder::table("users as u");
+        builder
+            .select(["u.name", "p.title"])
+            .left_join_lateral(
+                |b: &mut Builder| {
+                    b.select("title")
+                        .from("posts")
+                        .where_column("posts.user_id", "=", "u.id")
+                        .order_by_desc("id")
+                        .limit(1);
+                },
+                "p",
+                |_| {},
+            )
+            .join_lateral(
+                |b: &mut Builder| {
+                    b.select_one();
+                },
+                "x",
+                |join| {
+                    join.on("x.id", "=", "u.id");
+                },
+            );
+        assert_eq!(
+            r#"select "u"."name", "p"."title" from "users" as "u" left join lateral (select "title" from "posts" where "posts"."user_id" = "u"."id" order by "id" desc limit 1) as "p" on true inner join lateral (select 1) as "x" on "x"."id" = "u"."id""#,
+            builder.to_sql::<Postgres>()
+        );
+    }
+
+    #[test]
+    fn test_empty_in_list() {
+        let mut builder = Builder::table("users");
+        builder
+            .where_eq("active", true)
+            .where_in("id", Vec::<i64>::new())
+            .or_where_not_in("role", Vec::<&str>::new());
+        assert_eq!(
+            r#"select * from "users" where "active" = $1 and 1 = 0 or 1 = 1"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 1);
+        assert!(builder.try_to_sql::<MySql>().is_ok());
+
+        let mut builder = Builder::table("users");
+        assert!(matches!(
+            builder.where_in_strict("id", Vec::<i64>::new()),
+            Err(Error::EmptyInList)
+        ));
+        builder.where_in_strict("id", [1, 2]).unwrap();
+        assert_eq!(
+            "select * from `users` where `id` in (?, ?)",
+            builder.to_sql::<MySql>()
+        );
+    }
+
+    #[test]
+    fn test_in_array() {
+        let mut builder = Builder::table("users");
+        builder
+            .where_in_array("id", vec![1i64, 2, 3])
+            .or_where_not_in_array("role", ["admin", "owner"]);
+        assert_eq!(
+            r#"select * from "users" where "id" = any($1) or "role" <> all($2)"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 2);
+        assert_eq!(
+            "select * from `users` where `id` in (?, ?, ?) or `role` not in (?, ?)",
+            builder.to_sql::<MySql>()
+        );
+        assert_eq!(builder.build_sql::<MySql>().1.len(), 5);
+        assert_eq!(
+            r#"select * from "users" where "id" in (?1, ?2, ?3) or "role" not in (?4, ?5)"#,
+            builder.to_sql::<Sqlite>()
+        );
+
+        let mut builder = Builder::table("users");
+        builder.where_in_array("id", Vec::<i32>::new());
+        assert_eq!(
+            r#"select * from "users" where 1 = 0"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert!(builder.where_in_strict("id", Vec::<i32>::new()).is_err());
+    }
+
+    #[test]
+    fn test_try_to_sql() {
+        let mut builder = Builder::table("users");
+        builder.where_exists(|b: &mut Builder| {
+            b.from("posts").join_lateral(
+                |b: &mut Builder| {
+                    b.select_one();
+                },
+                "x",
+                |_| {},
+            );
+        });
+        assert!(matches!(
+            builder.try_build_sql::<Sqlite>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let mut builder = Builder::table("users");
+        builder.where_in("id", [1, 2]);
+        assert_eq!(
+            builder.to_sql::<MySql>(),
+            builder.try_to_sql::<MySql>().unwrap()
+        );
+
+        let mut builder = Builder::table("users");
+        builder.update_query(Dialect::MySql, Row::new());
+        assert!(matches!(
+            builder.try_to_sql::<MySql>(),
+            Err(Error::EmptyUpdate)
+        ));
+
+        let mut builder = Builder::table("users");
+        builder.join_lateral(
+            |b: &mut Builder| {
+                b.select_one();
+            },
+            "x",
+            |_| {},
+        );
+        assert!(matches!(
+            builder.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported {
+                dialect: Dialect::Sqlite,
+                ..
+            })
+        ));
+        assert!(builder.try_to_sql::<Postgres>().is_ok());
+
+        let mut builder = Builder::table("users");
+        builder
+            .select_count("id")
+            .full_join("posts", "users.id", "=", "posts.user_id")
+            .group_by("users.id");
+        assert!(builder.try_to_sql::<MySql>().is_err());
+        assert!(builder.try_to_sql::<Sqlite>().is_err());
+        assert!(builder.try_to_sql::<Postgres>().is_ok());
+
+        // nested queries render in the same dialect
+        let lateral = |b: &mut Builder| {
+            b.from("users").join_lateral(
+                |b: &mut Builder| {
+                    b.select_one();
+                },
+                "x",
+                |_| {},
+            );
+        };
+        let mut union = Builder::table("admins");
+        union.union(lateral);
+        let mut cte = Builder::table("recent");
+        cte.with("recent", lateral);
+        let mut from = Builder::new();
+        from.from_sub(lateral, "u");
+        let mut join = Builder::table("posts");
+        join.join_sub(lateral, "u", |j| {
+            j.on("u.id", "=", "posts.user_id");
+        });
+        let mut projection = Builder::table("posts");
+        projection.select(crate::sub_as(lateral, "u"));
+        for builder in [union, cte, from, join, projection] {
+            assert!(matches!(
+                builder.try_to_sql::<Sqlite>(),
+                Err(Error::Unsupported { .. })
+            ));
+            assert!(builder.try_to_sql::<Postgres>().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_u64_out_of_range() {
+        let mut builder = Builder::table("users");
+        builder.where_eq("id", u64::MAX);
+        assert!(matches!(
+            builder.try_build_sql::<Postgres>(),
+            Err(Error::BindOutOfRange { .. })
+        ));
+        assert!(matches!(
+            builder.try_build_sql::<Sqlite>(),
+            Err(Error::BindOutOfRange { .. })
+        ));
+        assert!(builder.try_build_sql::<MySql>().is_ok());
+        // the sql alone carries no value
+        assert_eq!(
+            r#"select * from "users" where "id" = $1"#,
+            builder.to_sql::<Postgres>()
+        );
+
+        let mut builder = Builder::table("users");
+        builder.where_raw("id = ?", u64::MAX);
+        assert!(matches!(
+            builder.try_build_sql::<Sqlite>(),
+            Err(Error::BindOutOfRange { .. })
+        ));
+
+        let mut builder = Builder::table("users");
+        builder.where_eq("id", i64::MAX as u64);
+        assert!(builder.try_build_sql::<Postgres>().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "bind does not fit a signed 64 bits integer on Sqlite")]
+    fn test_u64_out_of_range_panics() {
+        let mut builder = Builder::table("users");
+        builder.where_eq("id", u64::MAX);
+        builder.build_sql::<Sqlite>();
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_add_binding() {
+        let mut builder = Builder::table("users");
+        builder
+            .where_eq("name", "ovior")
+            .where_raw("id = ?", Binds::None)
+            .add_binding(1);
+        let (sql, binds) = builder.build_sql::<Sqlite>();
+        assert_eq!(r#"select * from "users" where "name" = ?1 and id = ?"#, sql);
+        assert_eq!(2, binds.len());
+        assert_eq!(1, builder.bindings().len());
+
+        builder.reset_bindings();
+        assert_eq!(1, builder.build_sql::<Sqlite>().1.len());
     }
 
     #[test]
@@ -1740,7 +2919,7 @@ mod tests {
             builder.to_sql::<Postgres>()
         );
 
-        assert_eq!(builder.binds.len(), 2);
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 2);
     }
 
     #[test]
@@ -1749,7 +2928,7 @@ mod tests {
         builder
             .where_eq("id", 1)
             .join("contacts", "users.id", "=", "contacts.user_id");
-        builder.delete_query::<MySql>();
+        builder.delete_query(Dialect::MySql);
         assert_eq!(
             r#"delete `users` from `users` inner join `contacts` on `users`.`id` = `contacts`.`user_id` where `id` = ?"#,
             builder.to_sql::<MySql>()
@@ -1758,23 +2937,23 @@ mod tests {
         builder
             .where_eq("id", 1)
             .join("contacts", "users.id", "=", "contacts.user_id");
-        builder.delete_query::<Postgres>();
+        builder.delete_query(Dialect::Postgres);
         assert_eq!(
-            r#"delete from "users" where "ctid" in (select "users"."ctid" from "users" inner join "contacts" on "users"."id" = "contacts"."user_id" where "id" = $1)"#,
+            r#"delete from "users" using "contacts" where "users"."id" = "contacts"."user_id" and "id" = $1"#,
             builder.to_sql::<Postgres>()
         );
         let mut builder = Builder::table("users");
         builder
             .where_eq("id", 1)
             .join("contacts", "users.id", "=", "contacts.user_id");
-        builder.delete_query::<Sqlite>();
+        builder.delete_query(Dialect::Sqlite);
         assert_eq!(
             r#"delete from "users" where "rowid" in (select "users"."rowid" from "users" inner join "contacts" on "users"."id" = "contacts"."user_id" where "id" = ?1)"#,
             builder.to_sql::<Sqlite>()
         );
         let mut builder = Builder::table("roles as r");
         builder.left_join("contacts", "users.id", "=", "contacts.user_id");
-        builder.delete_query::<Postgres>();
+        builder.delete_query(Dialect::Postgres);
         assert_eq!(
             r#"delete from "roles" as "r" where "ctid" in (select "r"."ctid" from "roles" as "r" left join "contacts" on "users"."id" = "contacts"."user_id")"#,
             builder.to_sql::<Postgres>(),
@@ -1782,10 +2961,235 @@ mod tests {
     }
 
     #[test]
-    fn test_insert_query() {
-        let mut builder = Builder::table("users");
-        let insert = builder
-            .inserting()
+    fn test_mutation_strategy() {
+        let row = Row::new().field("votes", 1).build();
+        let mut builder = Builder::table("users as u");
+        builder
+            .join("contacts as c", "u.id", "=", "c.user_id")
+            .where_eq("c.active", true)
+            .or_where_eq("c.admin", true)
+            .update_query(Dialect::Postgres, row.clone());
+        assert_eq!(
+            r#"update "users" as "u" set "votes" = $1 from "contacts" as "c" where "u"."id" = "c"."user_id" and ("c"."active" = $2 or "c"."admin" = $3)"#,
+            builder.to_sql::<Postgres>()
+        );
+
+        let mut builder = Builder::table("users");
+        builder
+            .join("contacts", "users.id", "=", "contacts.user_id")
+            .where_raw("contacts.active = 0 or users.name = 'c'", Binds::None)
+            .delete_query(Dialect::Postgres);
+        assert_eq!(
+            r#"delete from "users" using "contacts" where "users"."id" = "contacts"."user_id" and (contacts.active = 0 or users.name = 'c')"#,
+            builder.to_sql::<Postgres>()
+        );
+
+        let mut builder = Builder::table("users as u");
+        builder
+            .mutation_strategy(MutationStrategy::RowId)
+            .join("contacts as c", "u.id", "=", "c.user_id")
+            .update_query(Dialect::Postgres, row.clone());
+        assert_eq!(
+            r#"update "users" as "u" set "votes" = $1 where "ctid" in (select "u"."ctid" from "users" as "u" inner join "contacts" as "c" on "u"."id" = "c"."user_id")"#,
+            builder.to_sql::<Postgres>()
+        );
+
+        let mut builder = Builder::table("users as u");
+        builder
+            .mutation_strategy(MutationStrategy::PrimaryKey(Ident::new_static("id")))
+            .join("contacts as c", "u.id", "=", "c.user_id")
+            .where_eq("c.active", true)
+            .delete_query(Dialect::Sqlite);
+        assert_eq!(
+            r#"delete from "users" as "u" where "id" in (select "u"."id" from "users" as "u" inner join "contacts" as "c" on "u"."id" = "c"."user_id" where "c"."active" = ?1)"#,
+            builder.to_sql::<Sqlite>()
+        );
+
+        // mysql always joins natively
+        let mut builder = Builder::table("users");
+        builder
+            .mutation_strategy(MutationStrategy::RowId)
+            .join("contacts", "users.id", "=", "contacts.user_id")
+            .delete_query(Dialect::MySql);
+        assert_eq!(
+            "delete `users` from `users` inner join `contacts` on `users`.`id` = `contacts`.`user_id`",
+            builder.to_sql::<MySql>()
+        );
+    }
+
+    #[test]
+    fn test_returning() {
+        let mut builder = Builder::table("users");
+        builder.where_eq("id", 1).returning(["id", "username"]);
+        builder.delete_query(Dialect::Postgres);
+        assert_eq!(
+            r#"delete from "users" where "id" = $1 returning "id", "username""#,
+            builder.to_sql::<Postgres>()
+        );
+
+        let mut builder = Builder::table("users");
+        builder.where_eq("id", 1).returning("*");
+        builder.delete_query(Dialect::MySql);
+        assert_eq!(
+            "delete `users` from `users` where `id` = ?",
+            builder.to_sql::<MySql>()
+        );
+
+        let mut builder = Builder::table("users as u");
+        let row = Row::new().field("votes", 1).build();
+        builder
+            .where_eq("id", 1)
+            .join("contacts as c", "u.id", "=", "c.user_id")
+            .returning("*")
+            .update_query(Dialect::Sqlite, row.clone());
+        assert_eq!(
+            r#"update "users" as "u" set "votes" = ?1 from "contacts" as "c" where "u"."id" = "c"."user_id" and "id" = ?2 returning *"#,
+            builder.to_sql::<Sqlite>()
+        );
+
+        let mut builder = Builder::table("users");
+        builder
+            .where_eq("id", 1)
+            .returning("votes")
+            .update_query(Dialect::Postgres, row);
+        assert_eq!(
+            r#"update "users" set "votes" = $1 where "id" = $2 returning "votes""#,
+            builder.to_sql::<Postgres>()
+        );
+    }
+
+    #[test]
+    fn test_build_is_repeatable() {
+        let mut builder = Builder::table("users");
+        builder
+            .where_eq("id", 1)
+            .update_query(Dialect::Postgres, Row::new().field("name", "bob").build());
+        let (sql, binds) = builder.build_sql::<Postgres>();
+        assert_eq!(r#"update "users" set "name" = $1 where "id" = $2"#, sql);
+        let binds = binds.into_vec();
+        assert!(matches!(binds[0], Bind::StaticString(Some("bob"))));
+        assert!(matches!(binds[1], Bind::I32(Some(1))));
+
+        let (again, binds) = builder.build_sql::<Postgres>();
+        assert_eq!(sql, again);
+        assert_eq!(2, binds.len());
+
+        let mut builder = Builder::table("users");
+        builder
+            .select_raw("price + ? as fee", [5])
+            .where_eq("id", 1);
+        assert_eq!(
+            r#"select price + ? as fee from "users" where "id" = ?2"#,
+            builder.to_sql::<Sqlite>()
+        );
+        assert_eq!(2, builder.clone().build_sql::<Sqlite>().1.len());
+    }
+
+    #[test]
+    fn test_lock() {
+        let mut builder = Builder::table("jobs");
+        builder
+            .where_eq("status", "queued")
+            .order_by_asc("id")
+            .limit(10)
+            .lock_for_update()
+            .skip_locked();
+        assert_eq!(
+            r#"select * from "jobs" where "status" = $1 order by "id" asc limit 10 for update skip locked"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            "select * from `jobs` where `status` = ? order by `id` asc limit 10 for update skip locked",
+            builder.to_sql::<MySql>()
+        );
+        assert_eq!(
+            r#"select * from "jobs" where "status" = ?1 order by "id" asc limit 10"#,
+            builder.to_sql::<Sqlite>()
+        );
+
+        let mut builder = Builder::table("jobs");
+        builder.shared_lock();
+        assert_eq!(
+            r#"select * from "jobs" for share"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            "select * from `jobs` lock in share mode",
+            builder.to_sql::<MySql>()
+        );
+        builder.no_wait();
+        assert_eq!(
+            "select * from `jobs` for share nowait",
+            builder.to_sql::<MySql>()
+        );
+
+        let mut builder = Builder::table("jobs");
+        builder
+            .join("users", "users.id", "=", "jobs.user_id")
+            .lock_for_update()
+            .of("jobs");
+        assert_eq!(
+            r#"select * from "jobs" inner join "users" on "users"."id" = "jobs"."user_id" for update of "jobs""#,
+            builder.to_sql::<Postgres>()
+        );
+        builder.reset_lock();
+        assert_eq!(
+            r#"select * from "jobs" inner join "users" on "users"."id" = "jobs"."user_id""#,
+            builder.to_sql::<Postgres>()
+        );
+
+        // the options alone do not lock
+        let mut builder = Builder::table("jobs");
+        builder.of("jobs").skip_locked();
+        assert_eq!(r#"select * from "jobs""#, builder.to_sql::<Postgres>());
+        builder.lock_for_update();
+        assert_eq!(
+            r#"select * from "jobs" for update of "jobs" skip locked"#,
+            builder.to_sql::<Postgres>()
+        );
+
+        builder.strict_lock();
+        assert!(builder.try_to_sql::<Postgres>().is_ok());
+        assert!(matches!(
+            builder.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported {
+                dialect: Dialect::Sqlite,
+                ..
+            })
+        ));
+
+        // postgres rejects for update next to a union
+        let mut builder = Builder::table("jobs");
+        builder
+            .union(|b: &mut Builder| {
+                b.from("archived_jobs");
+            })
+            .lock_for_update();
+        for dialect in [Dialect::Postgres, Dialect::MySql] {
+            assert!(matches!(
+                builder.try_to_sql_for(dialect),
+                Err(Error::Unsupported { .. })
+            ));
+        }
+        assert!(builder.try_to_sql::<Sqlite>().is_ok());
+        builder.reset_lock();
+        assert!(builder.try_to_sql::<Postgres>().is_ok());
+
+        let mut builder = Builder::table("jobs");
+        builder.union(|b: &mut Builder| {
+            b.from("archived_jobs").lock_for_update();
+        });
+        assert!(matches!(
+            builder.try_to_sql::<Postgres>(),
+            Err(Error::Unsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn test_insert_query() {
+        let mut builder = Builder::table("users");
+        let insert = builder
+            .inserting()
             .row(|row: &mut Row| {
                 row.field("id", 1).field("username", "ovior");
             })
@@ -1799,7 +3203,7 @@ mod tests {
 
     #[test]
     fn test_empty_table() {
-        let mut builder = Builder::table("");
+        let builder = Builder::table("");
         assert_eq!("select * from \"\"", builder.to_sql::<Postgres>());
     }
 
@@ -1807,7 +3211,9 @@ mod tests {
     fn test_update_query() {
         let mut builder = Builder::table("users");
         let row = Row::new().field("votes", 1).build();
-        builder.where_eq("id", 1).update_query::<Postgres>(row);
+        builder
+            .where_eq("id", 1)
+            .update_query(Dialect::Postgres, row);
         assert_eq!(
             "update \"users\" set \"votes\" = $1 where \"id\" = $2",
             builder.to_sql::<Postgres>()
@@ -1821,26 +3227,26 @@ mod tests {
         builder
             .where_eq("id", 1)
             .join("contacts as c", "u.id", "=", "c.user_id")
-            .update_query::<Postgres>(row.clone());
+            .update_query(Dialect::Postgres, row.clone());
 
         assert_eq!(
-            r#"update "users" as "u" set "votes" = $1 where "ctid" in (select "u"."ctid" from "users" as "u" inner join "contacts" as "c" on "u"."id" = "c"."user_id" where "id" = $2)"#,
+            r#"update "users" as "u" set "votes" = $1 from "contacts" as "c" where "u"."id" = "c"."user_id" and "id" = $2"#,
             builder.to_sql::<Postgres>()
         );
         let mut builder = Builder::table("users as u");
         builder
             .where_eq("id", 1)
             .join("contacts as c", "u.id", "=", "c.user_id")
-            .update_query::<Sqlite>(row.clone());
+            .update_query(Dialect::Sqlite, row.clone());
         assert_eq!(
-            r#"update "users" as "u" set "votes" = ?1 where "rowid" in (select "u"."rowid" from "users" as "u" inner join "contacts" as "c" on "u"."id" = "c"."user_id" where "id" = ?2)"#,
+            r#"update "users" as "u" set "votes" = ?1 from "contacts" as "c" where "u"."id" = "c"."user_id" and "id" = ?2"#,
             builder.to_sql::<Sqlite>()
         );
         let mut builder = Builder::table("users as u");
         builder
             .where_eq("id", 1)
             .join("contacts as c", "u.id", "=", "c.user_id")
-            .update_query::<MySql>(row);
+            .update_query(Dialect::MySql, row);
         assert_eq!(
             r#"update `users` as `u` inner join `contacts` as `c` on `u`.`id` = `c`.`user_id` set `votes` = ? where `id` = ?"#,
             builder.to_sql::<MySql>()
@@ -1854,7 +3260,7 @@ mod tests {
         builder
             .where_eq("id", 1)
             .order_by_asc("id")
-            .update_query::<MySql>(row);
+            .update_query(Dialect::MySql, row);
         assert_eq!(
             "update \"users\" set \"unknown_field\" = $1 where \"id\" = $2",
             builder.to_sql::<Postgres>()
@@ -1868,7 +3274,7 @@ mod tests {
         builder
             .where_eq("id", 1)
             .order_by_asc("id")
-            .update_query::<Postgres>(row);
+            .update_query(Dialect::Postgres, row);
         assert_eq!(
             "update \"users\" set \"unknown_field\" = $1 where \"id\" = $2",
             builder.to_sql::<Postgres>()
@@ -1878,7 +3284,7 @@ mod tests {
         builder
             .where_eq("id", 1)
             .order_by_asc("id")
-            .update_query::<Sqlite>(row);
+            .update_query(Dialect::Sqlite, row);
         assert_eq!(
             "update \"users\" set \"unknown_field\" = ?1 where \"id\" = ?2",
             builder.to_sql::<Sqlite>()
@@ -1893,7 +3299,7 @@ mod tests {
             .where_eq("id", 1)
             .join("contacts as c", "u.id", "=", "c.user_id")
             .order_by_asc("id")
-            .update_query::<Postgres>(row);
+            .update_query(Dialect::Postgres, row);
         assert_eq!(
             r#"update "users" as "u" set "votes" = $1 where "ctid" in (select "u"."ctid" from "users" as "u" inner join "contacts" as "c" on "u"."id" = "c"."user_id" where "id" = $2 order by "id" asc)"#,
             builder.to_sql::<Postgres>()
@@ -1944,4 +3350,443 @@ mod tests {
             builder.to_sql::<Postgres>()
         );
     }
+
+    #[test]
+    fn test_with_clause() {
+        let mut builder = Builder::table("active");
+        builder
+            .where_eq("name", "bob")
+            .with("active", |builder: &mut Builder| {
+                builder.from("users").where_eq("active", true);
+            })
+            .with_materialized("admins", |builder: &mut Builder| {
+                builder.from("users").where_eq("role", "admin");
+            });
+        assert_eq!(
+            r#"with "active" as (select * from "users" where "active" = $1), "admins" as materialized (select * from "users" where "role" = $2) select * from "active" where "name" = $3"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            r#"with `active` as (select * from `users` where `active` = ?), `admins` as (select * from `users` where `role` = ?) select * from `active` where `name` = ?"#,
+            builder.to_sql::<MySql>()
+        );
+        let binds = builder.build_sql::<Postgres>().1.into_vec();
+        assert!(matches!(binds[0], Bind::Bool(Some(true))));
+        assert!(matches!(binds[1], Bind::StaticString(Some("admin"))));
+        assert!(matches!(binds[2], Bind::StaticString(Some("bob"))));
+    }
+
+    #[test]
+    fn test_with_recursive() {
+        let mut builder = Builder::table("tree");
+        builder.with_recursive(
+            "tree",
+            ["id", "parent_id"],
+            |builder: &mut Builder| {
+                builder
+                    .select(["id", "parent_id"])
+                    .from("categories")
+                    .where_eq("id", 1);
+            },
+            |builder: &mut Builder| {
+                builder
+                    .select(["c.id", "c.parent_id"])
+                    .from("categories as c")
+                    .join("tree as t", "t.id", "=", "c.parent_id");
+            },
+        );
+        assert_eq!(
+            r#"with recursive "tree" ("id", "parent_id") as (select "id", "parent_id" from "categories" where "id" = ?1 union all select "c"."id", "c"."parent_id" from "categories" as "c" inner join "tree" as "t" on "t"."id" = "c"."parent_id") select * from "tree""#,
+            builder.to_sql::<Sqlite>()
+        );
+        builder.reset_with();
+        assert!(builder.build_sql::<Postgres>().1.is_empty());
+        assert_eq!(r#"select * from "tree""#, builder.to_sql::<Sqlite>());
+    }
+
+    #[test]
+    fn test_union() {
+        let mut builder = Builder::table("users");
+        builder
+            .select("id")
+            .where_eq("active", true)
+            .union(|builder: &mut Builder| {
+                builder
+                    .select("id")
+                    .from("admins")
+                    .where_eq("role", "owner");
+            })
+            .union_all(|builder: &mut Builder| {
+                builder.select("id").from("guests");
+            });
+        assert_eq!(
+            r#"select "id" from "users" where "active" = $1 union select "id" from "admins" where "role" = $2 union all select "id" from "guests""#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 2);
+    }
+
+    #[test]
+    fn test_union_order_limit() {
+        let mut builder = Builder::table("users");
+        builder
+            .select("id")
+            .order_by_desc("id")
+            .limit(5)
+            .except(|builder: &mut Builder| {
+                builder.select("id").from("banned").where_eq("id", 1);
+            })
+            .order_by_asc("id")
+            .limit(10)
+            .offset(2);
+        assert_eq!(
+            r#"(select "id" from "users" order by "id" desc limit 5) except select "id" from "banned" where "id" = $1 order by "id" asc limit 10 offset 2"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            r#"select * from (select "id" from "users" order by "id" desc limit 5) except select "id" from "banned" where "id" = ?1 order by "id" asc limit 10 offset 2"#,
+            builder.to_sql::<Sqlite>()
+        );
+
+        let mut builder = Builder::table("users");
+        builder
+            .select("id")
+            .intersect(|builder: &mut Builder| {
+                builder.select("id").from("admins").latest("id").limit(1);
+            })
+            .reorder()
+            .reset_limit();
+        assert_eq!(
+            r#"select `id` from `users` intersect (select `id` from `admins` order by `id` desc limit 1)"#,
+            builder.to_sql::<MySql>()
+        );
+    }
+
+    #[test]
+    fn test_window_functions() {
+        use crate::{
+            expr::window::{FrameBound, Window},
+            lag_or, row_number, sum,
+        };
+
+        let mut builder = Builder::table("employees");
+        builder
+            .select("name")
+            .add_select(row_number().over(|w: &mut Window| {
+                w.partition_by("department").order_by_desc("salary");
+            }))
+            .add_select(sum("salary as running").over(|w: &mut Window| {
+                w.order_by_asc("hired_at")
+                    .rows_between(FrameBound::UnboundedPreceding, FrameBound::CurrentRow);
+            }))
+            .add_select(lag_or("salary", 1, 0).over("w").alias("previous"))
+            .window("w", |w| {
+                w.partition_by("department");
+            })
+            .order_by_asc(row_number().over("w").alias("ignored"));
+        assert_eq!(
+            r#"select "name", row_number() over (partition by "department" order by "salary" desc), sum("salary") over (order by "hired_at" asc rows between unbounded preceding and current row) as "running", lag("salary", 1, $1) over "w" as "previous" from "employees" window "w" as (partition by "department") order by row_number() over "w" asc"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(builder.build_sql::<Postgres>().1.len(), 1);
+    }
+
+    #[test]
+    fn test_function_calls() {
+        use crate::{
+            coalesce, column, func, length, lower, now, nullif, round_to, substring, upper,
+        };
+
+        let mut builder = Builder::table("users");
+        builder
+            .select(lower("email").alias("email"))
+            .add_select(coalesce("nickname", "anonymous").alias("nickname"))
+            .add_select(round_to("score", 2))
+            .add_select(func("date", [column("created_at")]).alias("day"))
+            .where_eq(upper("country"), "CA")
+            .where_clause(length("name"), ">", 3)
+            .where_not_null(nullif("bio", ""))
+            .where_eq(
+                "team_id",
+                func(
+                    "coalesce",
+                    (
+                        sub(|builder| {
+                            builder.select("id").from("teams").where_eq("name", "core");
+                        }),
+                        column("default_team_id"),
+                        0,
+                    ),
+                ),
+            );
+        assert_eq!(
+            r#"select lower("email") as "email", coalesce("nickname", $1) as "nickname", round("score", 2), date("created_at") as "day" from "users" where upper("country") = $2 and length("name") > $3 and nullif("bio", $4) is not null and "team_id" = coalesce((select "id" from "teams" where "name" = $5), "default_team_id", $6)"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert!(
+            builder
+                .to_sql::<MySql>()
+                .contains("and char_length(`name`) > ?")
+        );
+        assert_eq!(6, builder.build_sql::<Postgres>().1.len());
+
+        let mut builder = Builder::table("users");
+        builder
+            .select(substring("name", 1, 3).alias("prefix"))
+            .add_select(length("name"))
+            .add_select(coalesce(column("nickname"), column("name")))
+            .add_select(func("ifnull", (column("nickname"), column("name"))))
+            .where_clause("created_at", "<", now());
+        assert_eq!(
+            r#"select substring("name", $1, $2) as "prefix", length("name"), coalesce("nickname", "name"), ifnull("nickname", "name") from "users" where "created_at" < now()"#,
+            builder.try_to_sql::<Postgres>().unwrap()
+        );
+        assert_eq!(
+            r#"select substr("name", ?1, ?2) as "prefix", length("name"), coalesce("nickname", "name"), ifnull("nickname", "name") from "users" where "created_at" < datetime('now')"#,
+            builder.try_to_sql::<Sqlite>().unwrap()
+        );
+        assert_eq!(
+            "select substring(`name`, ?, ?) as `prefix`, char_length(`name`), coalesce(`nickname`, `name`), ifnull(`nickname`, `name`) from `users` where `created_at` < now()",
+            builder.try_to_sql::<MySql>().unwrap()
+        );
+
+        // named functions are written as given on every dialect
+        let mut builder = Builder::table("events");
+        builder.select(func("now", ()));
+        assert_eq!(
+            r#"select now() from "events""#,
+            builder.try_to_sql::<Sqlite>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        use crate::{column, concat, lower};
+
+        let total = column("price") * column("qty");
+        let mut builder = Builder::table("items");
+        builder
+            .select(total.clone() - column("discount"))
+            .where_clause(total, ">", 100)
+            .where_eq(
+                (column("a") + column("b")) * 2,
+                column("c") - (column("d") - 1),
+            )
+            .where_eq(-(column("e") % 3), -column("f"))
+            .where_eq(
+                concat((lower("first"), " ", column("last"))),
+                concat((column("x"), column("y") + 1)),
+            );
+        assert_eq!(
+            r#"select "price" * "qty" - "discount" from "items" where "price" * "qty" > $1 and ("a" + "b") * $2 = "c" - ("d" - $3) and -("e" % $4) = -"f" and lower("first") || $5 || "last" = "x" || ("y" + $6)"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert!(
+            builder
+                .to_sql::<MySql>()
+                .ends_with("and concat(lower(`first`), ?, `last`) = concat(`x`, `y` + ?)")
+        );
+
+        let mut builder = Builder::table("items");
+        builder.where_eq("name", concat(Vec::<Expr>::new()));
+        assert_eq!(
+            r#"select * from "items" where "name" = ''"#,
+            builder.to_sql::<Postgres>()
+        );
+
+        let mut builder = Builder::table("items");
+        builder
+            .select(-raw("-1").into_rhs_expr())
+            .where_eq(-(-column("a")), 1);
+        assert_eq!(
+            r#"select -(-1) from "items" where -(-"a") = $1"#,
+            builder.to_sql::<Postgres>()
+        );
+    }
+
+    #[test]
+    fn test_case_when() {
+        use crate::{case, case_on, column};
+
+        let mut builder = Builder::table("users");
+        builder
+            .select("name")
+            .add_select(
+                case()
+                    .when(
+                        |b| {
+                            b.where_eq("votes", 0);
+                        },
+                        "none",
+                    )
+                    .when(
+                        |b| {
+                            b.where_between("votes", 1, 10).or_where_null("rank");
+                        },
+                        "few",
+                    )
+                    .otherwise("many")
+                    .alias("bucket"),
+            )
+            .where_eq("active", true)
+            .order_by_asc(
+                case_on("role")
+                    .when("admin", 1)
+                    .when("owner", column("owner_rank"))
+                    .otherwise(3)
+                    .alias("ignored"),
+            );
+        assert_eq!(
+            r#"select "name", case when "votes" = $1 then $2 when "votes" between $3 and $4 or "rank" is null then $5 else $6 end as "bucket" from "users" where "active" = $7 order by case "role" when $8 then $9 when $10 then "owner_rank" else $11 end asc"#,
+            builder.to_sql::<Postgres>()
+        );
+        let binds = builder.build_sql::<Postgres>().1.into_vec();
+        assert_eq!(11, binds.len());
+        assert!(matches!(binds[6], Bind::Bool(Some(true))));
+        assert!(matches!(binds[7], Bind::StaticString(Some("admin"))));
+    }
+
+    #[test]
+    fn test_aggregate_expressions() {
+        use crate::{array_agg, bool_or, column, count, string_agg, sum};
+
+        let mut builder = Builder::table("orders");
+        builder
+            .select(count("user_id as buyers").distinct())
+            .add_select(sum(column("price") * column("qty")).alias("revenue"))
+            .add_select(count('*').filter(|w| {
+                w.where_eq("status", "paid");
+            }))
+            .group_by("day");
+        assert_eq!(
+            r#"select count(distinct "user_id") as "buyers", sum("price" * "qty") as "revenue", count(*) filter (where "status" = $1) from "orders" group by "day""#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            "select count(distinct `user_id`) as `buyers`, sum(`price` * `qty`) as `revenue`, count(case when `status` = ? then 1 end) from `orders` group by `day`",
+            builder.to_sql::<MySql>()
+        );
+
+        let mut builder = Builder::table("orders");
+        builder
+            .select(sum("total").filter(|w| {
+                w.where_eq("status", "paid");
+            }))
+            .add_select(string_agg("name as names", "', "))
+            .add_select(array_agg("id").distinct())
+            .add_select(bool_or("refunded"));
+        assert_eq!(
+            r#"select sum("total") filter (where "status" = $1), string_agg("name", ''', ') as "names", array_agg(distinct "id"), bool_or("refunded") from "orders""#,
+            builder.to_sql::<Postgres>()
+        );
+        assert!(matches!(
+            builder.try_to_sql::<MySql>(),
+            Err(Error::Unsupported { .. })
+        ));
+        assert_eq!(
+            r#"select sum(case when "status" = ?1 then "total" end), group_concat("name", ''', ') as "names", json_group_array(distinct "id"), max("refunded") from "orders""#,
+            builder.try_to_sql::<Sqlite>().unwrap()
+        );
+
+        let mut builder = Builder::table("orders");
+        builder.select(string_agg("name", ",").distinct());
+        assert_eq!(
+            r#"select group_concat(distinct "name") from "orders""#,
+            builder.try_to_sql::<Sqlite>().unwrap()
+        );
+        let mut builder = Builder::table("orders");
+        builder.select(string_agg("name", ", ").distinct());
+        assert!(matches!(
+            builder.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported { .. })
+        ));
+        assert_eq!(
+            "select group_concat(distinct `name` separator ', ') from `orders`",
+            builder.try_to_sql::<MySql>().unwrap()
+        );
+
+        // a backslash would read differently with NO_BACKSLASH_ESCAPES
+        let mut builder = Builder::table("orders");
+        builder.select(string_agg("name", "\\n"));
+        assert_eq!(
+            "select group_concat(`name` separator x'5c6e') from `orders`",
+            builder.try_to_sql::<MySql>().unwrap()
+        );
+        assert_eq!(
+            r#"select group_concat("name", '\n') from "orders""#,
+            builder.try_to_sql::<Sqlite>().unwrap()
+        );
+        let mut builder = Builder::table("orders");
+        builder.select(string_agg("name", "it's"));
+        assert_eq!(
+            "select group_concat(`name` separator 'it''s') from `orders`",
+            builder.try_to_sql::<MySql>().unwrap()
+        );
+
+        let mut builder = Builder::table("orders");
+        builder
+            .add_select_max("total")
+            .add_select_sum("total")
+            .add_select_avg("total");
+        assert_eq!(
+            r#"select max("total"), sum("total"), avg("total") from "orders""#,
+            builder.to_sql::<Postgres>()
+        );
+    }
+
+    #[test]
+    fn test_cast() {
+        use crate::{cast, expr::cast::SqlType};
+
+        let mut builder = Builder::table("orders");
+        builder
+            .select(cast("total", SqlType::Decimal(10, 2)))
+            .add_select(cast("id", SqlType::Text))
+            .where_eq(cast("created_at", SqlType::Date), "2025-01-02")
+            .where_clause(cast("votes", SqlType::BigInt), ">", 3);
+        assert_eq!(
+            r#"select cast("total" as numeric(10, 2)), cast("id" as text) from "orders" where cast("created_at" as date) = $1 and cast("votes" as bigint) > $2"#,
+            builder.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            "select cast(`total` as decimal(10, 2)), cast(`id` as char) from `orders` where cast(`created_at` as date) = ? and cast(`votes` as signed) > ?",
+            builder.to_sql::<MySql>()
+        );
+        assert_eq!(
+            r#"select cast("total" as numeric), cast("id" as text) from "orders" where cast("created_at" as text) = ?1 and cast("votes" as integer) > ?2"#,
+            builder.to_sql::<Sqlite>()
+        );
+
+        let mut builder = Builder::table("users");
+        builder.where_like(cast("id", SqlType::Text), "1%");
+        assert_eq!(
+            r#"select * from "users" where cast("id" as text) like $1"#,
+            builder.to_sql::<Postgres>()
+        );
+    }
+
+    #[test]
+    fn test_window_sub_as() {
+        use crate::{expr::window::Window, rank, sub_as};
+
+        let mut builder = Builder::new();
+        builder
+            .from(sub_as(
+                |builder| {
+                    builder.from("scores").select("player").add_select(
+                        rank()
+                            .over(|w: &mut Window| {
+                                w.order_by_desc("points");
+                            })
+                            .alias("position"),
+                    );
+                },
+                "ranked",
+            ))
+            .where_clause("position", "<=", 3);
+        assert_eq!(
+            r#"select * from (select `player`, rank() over (order by `points` desc) as `position` from `scores`) as `ranked` where `position` <= ?"#,
+            builder.to_sql::<MySql>()
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use std::fmt;
 
 use crate::{
-    bind::{Array, Bind, Binds}, expr::{exists::ExistsExpr, fncall::AggregateCall, r#in::InExpr, Expr, TakeBindings}, ident::{Ident, IntoIdent, RawOrIdent, TableRef}, writer::FormatWriter, Builder, Raw
+    bind::{Array, Bind}, expr::{case::{Case, CaseOn}, exists::ExistsExpr, fncall::{AggregateCall, FunctionCall}, r#in::InExpr, window::WindowCall, Expr}, ident::{Ident, IntoIdent, RawOrIdent, TableRef}, writer::FormatWriter, Builder, Raw
 };
 
 pub type Projections = Array<Expr>;
@@ -283,7 +283,31 @@ impl IntoProjections for Expr {
 
 impl IntoProjections for AggregateCall {
     fn into_select_proj(self) -> Projections {
-        Projections::One(Expr::AggregateCall(self))
+        Projections::One(Expr::AggregateCall(Box::new(self)))
+    }
+}
+
+impl IntoProjections for FunctionCall {
+    fn into_select_proj(self) -> Projections {
+        Projections::One(Expr::Function(Box::new(self)))
+    }
+}
+
+impl IntoProjections for Case {
+    fn into_select_proj(self) -> Projections {
+        Projections::One(Expr::Case(Box::new(self.into_case_expr())))
+    }
+}
+
+impl IntoProjections for CaseOn {
+    fn into_select_proj(self) -> Projections {
+        Projections::One(Expr::Case(Box::new(self.into_case_expr())))
+    }
+}
+
+impl IntoProjections for WindowCall {
+    fn into_select_proj(self) -> Projections {
+        Projections::One(Expr::Window(Box::new(self)))
     }
 }
 
@@ -344,12 +368,6 @@ impl FormatWriter for AliasSub {
     }
 }
 
-impl TakeBindings for AliasSub {
-    fn take_bindings(&mut self) -> Binds {
-        self.inner.take_bindings()
-    }
-}
-
 pub trait IntoTable {
     fn into_table(self) -> TableRef;
 }
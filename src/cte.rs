@@ -0,0 +1,113 @@
+use crate::{
+    Builder, Ident,
+    dialect::Dialect,
+    insert::Columns,
+    writer::{FormatContext, FormatWriter},
+};
+
+#[derive(Debug, Clone)]
+pub struct CommonTable {
+    name: Ident,
+    maybe_columns: Option<Columns>,
+    materialized: bool,
+    query: Box<Builder>,
+    maybe_recursive: Option<Box<Builder>>,
+}
+
+impl CommonTable {
+    pub(crate) fn new(name: Ident, query: Builder) -> Self {
+        Self {
+            name,
+            maybe_columns: None,
+            materialized: false,
+            query: Box::new(query),
+            maybe_recursive: None,
+        }
+    }
+
+    pub(crate) fn materialized(mut self) -> Self {
+        self.materialized = true;
+        self
+    }
+
+    pub(crate) fn recursive(mut self, columns: Columns, recursive: Builder) -> Self {
+        if !columns.is_empty() {
+            self.maybe_columns = Some(columns);
+        }
+        self.maybe_recursive = Some(Box::new(recursive));
+        self
+    }
+
+    pub fn is_recursive(&self) -> bool {
+        self.maybe_recursive.is_some()
+    }
+}
+
+impl FormatWriter for CommonTable {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        self.name.format_writer(context)?;
+        if let Some(ref columns) = self.maybe_columns {
+            context.writer.write_str(" (")?;
+            columns.format_writer(context)?;
+            context.writer.write_char(')')?;
+        }
+        context.writer.write_str(" as ")?;
+        // mysql has no materialization hint, the optimizer decides
+        if self.materialized && matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite) {
+            context.writer.write_str("materialized ")?;
+        }
+        context.writer.write_char('(')?;
+        self.query.format_writer(context)?;
+        if let Some(ref recursive) = self.maybe_recursive {
+            context.writer.write_str(" union all ")?;
+            recursive.format_writer(context)?;
+        }
+        context.writer.write_char(')')
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct WithClause {
+    tables: Vec<CommonTable>,
+}
+
+impl WithClause {
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, table: CommonTable) {
+        self.tables.push(table);
+    }
+
+    // the bodies of every table, recursive parts included
+    pub(crate) fn queries(&self) -> impl Iterator<Item = &Builder> {
+        self.tables.iter().flat_map(|table| {
+            std::iter::once(table.query.as_ref()).chain(table.maybe_recursive.as_deref())
+        })
+    }
+}
+
+impl FormatWriter for WithClause {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        // the recursive keyword applies to the whole with list
+        if self.tables.iter().any(CommonTable::is_recursive) {
+            context.writer.write_str("with recursive ")?;
+        } else {
+            context.writer.write_str("with ")?;
+        }
+        for (index, table) in self.tables.iter().enumerate() {
+            if index > 0 {
+                context.writer.write_str(", ")?;
+            }
+            table.format_writer(context)?;
+        }
+        Ok(())
+    }
+}
@@ -5,10 +5,81 @@ pub enum Dialect {
     Sqlite,
 }
 
+impl Dialect {
+    /// Maps the backend name reported by sqlx, either a driver name such as
+    /// `PostgreSQL` or a connection url scheme such as `postgres`.
+    pub fn from_backend_name(name: &str) -> Option<Dialect> {
+        match name.to_ascii_lowercase().as_str() {
+            "postgres" | "postgresql" => Some(Dialect::Postgres),
+            "mysql" | "mariadb" => Some(Dialect::MySql),
+            "sqlite" => Some(Dialect::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// The most bind parameters a single statement can carry.
+    pub fn max_binds(self) -> usize {
+        match self {
+            Dialect::Postgres | Dialect::MySql => 65535,
+            // SQLITE_MAX_VARIABLE_NUMBER since 3.32
+            Dialect::Sqlite => 32766,
+        }
+    }
+}
+
 pub trait HasDialect {
     const DIALECT: Dialect;
 }
 
+/// Resolves the dialect of the database behind an executor. Typed pools know
+/// it at compile time while `sqlx::Any` pools only know it at runtime.
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
+pub trait HasExecutorDialect {
+    fn dialect(&self) -> Result<Dialect, sqlx::Error>;
+
+    /// Whether arrays must be sent one value per element, the driver has no
+    /// array type.
+    fn expands_arrays(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
+impl<DB> HasExecutorDialect for &sqlx::Pool<DB>
+where
+    DB: sqlx::Database + HasDialect,
+{
+    fn dialect(&self) -> Result<Dialect, sqlx::Error> {
+        Ok(DB::DIALECT)
+    }
+}
+
+#[cfg(feature = "any")]
+impl HasExecutorDialect for &sqlx::Pool<sqlx::Any> {
+    fn dialect(&self) -> Result<Dialect, sqlx::Error> {
+        // the any driver picks its backend from the url scheme as well
+        let options = self.connect_options();
+        let scheme = options.database_url.scheme();
+        Dialect::from_backend_name(scheme).ok_or_else(|| {
+            sqlx::Error::Configuration(format!("unsupported database backend: {scheme}").into())
+        })
+    }
+
+    fn expands_arrays(&self) -> bool {
+        true
+    }
+}
+
 pub struct Postgres;
 
 impl HasDialect for Postgres {
@@ -34,18 +105,51 @@ impl HasDialect for sqlx::Postgres {
 
 #[cfg(feature = "mysql")]
 impl HasDialect for sqlx::MySql {
-    const DIALECT: Dialect = Dialect::Postgres;
+    const DIALECT: Dialect = Dialect::MySql;
 }
 
 #[cfg(feature = "sqlite")]
 impl HasDialect for sqlx::Sqlite {
-    const DIALECT: Dialect = Dialect::Postgres;
+    const DIALECT: Dialect = Dialect::Sqlite;
 }
 
 pub trait HasRowsAffected {
     fn rows_affected(&self) -> usize;
 }
 
+pub trait HasLastInsertId {
+    fn last_insert_id(&self) -> Option<u64>;
+}
+
+#[cfg(feature = "mysql")]
+impl HasLastInsertId for sqlx::mysql::MySqlQueryResult {
+    fn last_insert_id(&self) -> Option<u64> {
+        Some(self.last_insert_id())
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl HasLastInsertId for sqlx::postgres::PgQueryResult {
+    fn last_insert_id(&self) -> Option<u64> {
+        // postgres only reports generated values through returning
+        None
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl HasLastInsertId for sqlx::sqlite::SqliteQueryResult {
+    fn last_insert_id(&self) -> Option<u64> {
+        Some(self.last_insert_rowid() as u64)
+    }
+}
+
+#[cfg(feature = "any")]
+impl HasLastInsertId for sqlx::any::AnyQueryResult {
+    fn last_insert_id(&self) -> Option<u64> {
+        self.last_insert_id().map(|id| id as u64)
+    }
+}
+
 #[cfg(feature = "mysql")]
 impl HasRowsAffected for sqlx::mysql::MySqlQueryResult {
     fn rows_affected(&self) -> usize {
@@ -66,3 +170,10 @@ impl HasRowsAffected for sqlx::sqlite::SqliteQueryResult {
         self.rows_affected() as usize
     }
 }
+
+#[cfg(feature = "any")]
+impl HasRowsAffected for sqlx::any::AnyQueryResult {
+    fn rows_affected(&self) -> usize {
+        self.rows_affected() as usize
+    }
+}
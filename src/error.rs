@@ -0,0 +1,118 @@
+use std::fmt;
+
+use crate::dialect::Dialect;
+
+/// Errors from validating a query before it is rendered or executed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An empty list given to [`Builder::where_in_strict`].
+    ///
+    /// [`Builder::where_in_strict`]: crate::Builder::where_in_strict
+    EmptyInList,
+    /// An update without any column to set.
+    EmptyUpdate,
+    /// An insert without rows nor select.
+    EmptyInsert,
+    /// An upsert missing its conflict columns or the columns to update.
+    IncompleteUpsert,
+    /// A merge missing its source, its on condition or a when clause.
+    IncompleteMerge,
+    /// A single statement needs more bind parameters than the dialect
+    /// accepts, see [`InsertBuilder::chunks_for`] to split large inserts.
+    ///
+    /// [`InsertBuilder::chunks_for`]: crate::InsertBuilder::chunks_for
+    TooManyBinds {
+        dialect: Dialect,
+        limit: usize,
+    },
+    /// A `u64` bind above `i64::MAX`, only mysql has unsigned 64 bits
+    /// integers.
+    BindOutOfRange {
+        dialect: Dialect,
+    },
+    /// The query uses something the dialect can not express.
+    Unsupported {
+        dialect: Dialect,
+        reason: &'static str,
+    },
+    Format(fmt::Error),
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    Sqlx(sqlx::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EmptyInList => f.write_str("in condition has an empty list"),
+            Error::EmptyUpdate => f.write_str("update has no column to set"),
+            Error::EmptyInsert => f.write_str("insert has no rows"),
+            Error::IncompleteUpsert => {
+                f.write_str("upsert needs conflict columns and columns to update")
+            }
+            Error::IncompleteMerge => {
+                f.write_str("merge needs a source, an on condition and a when clause")
+            }
+            Error::TooManyBinds { dialect, limit } => {
+                write!(f, "query has more than {} binds on {:?}", limit, dialect)
+            }
+            Error::BindOutOfRange { dialect } => {
+                write!(
+                    f,
+                    "bind does not fit a signed 64 bits integer on {:?}",
+                    dialect
+                )
+            }
+            Error::Unsupported { dialect, reason } => {
+                write!(f, "unsupported on {:?}: {}", dialect, reason)
+            }
+            Error::Format(err) => write!(f, "failed to render the query: {}", err),
+            #[cfg(any(
+                feature = "postgres",
+                feature = "sqlite",
+                feature = "mysql",
+                feature = "any"
+            ))]
+            Error::Sqlx(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Format(err) => Some(err),
+            #[cfg(any(
+                feature = "postgres",
+                feature = "sqlite",
+                feature = "mysql",
+                feature = "any"
+            ))]
+            Error::Sqlx(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(value: fmt::Error) -> Self {
+        Error::Format(value)
+    }
+}
+
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
+impl From<sqlx::Error> for Error {
+    fn from(value: sqlx::Error) -> Self {
+        Error::Sqlx(value)
+    }
+}
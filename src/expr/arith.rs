@@ -0,0 +1,216 @@
+use std::fmt::Write;
+
+use crate::{
+    Ident,
+    dialect::Dialect,
+    ident::TableRef,
+    writer::{FormatContext, FormatWriter},
+};
+
+use super::{Expr, IntoRhsExpr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Concat,
+}
+
+impl ArithOperator {
+    // concat binds differently on every dialect, it never shares a level
+    // with the arithmetic operators so mixing them always adds parentheses
+    fn precedence(self) -> u8 {
+        match self {
+            ArithOperator::Add | ArithOperator::Sub => 1,
+            ArithOperator::Mul | ArithOperator::Div | ArithOperator::Rem => 2,
+            ArithOperator::Concat => 0,
+        }
+    }
+}
+
+impl FormatWriter for ArithOperator {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        match self {
+            ArithOperator::Add => context.writer.write_char('+'),
+            ArithOperator::Sub => context.writer.write_char('-'),
+            ArithOperator::Mul => context.writer.write_char('*'),
+            ArithOperator::Div => context.writer.write_char('/'),
+            ArithOperator::Rem => context.writer.write_char('%'),
+            ArithOperator::Concat => context.writer.write_str("||"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ArithExpr {
+    pub(crate) lhs: Expr,
+    pub(crate) operator: ArithOperator,
+    pub(crate) rhs: Expr,
+}
+
+impl ArithExpr {
+    fn write_operand<W: Write>(
+        &self,
+        operand: &Expr,
+        is_rhs: bool,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        let wrap = match operand {
+            Expr::Arith(inner) if inner.operator == self.operator && self.is_concat() => false,
+            Expr::Arith(inner) if inner.is_concat() || self.is_concat() => true,
+            // same level on the right changes the meaning of a - (b - c)
+            Expr::Arith(inner) => {
+                let (inner, outer) = (inner.operator.precedence(), self.operator.precedence());
+                inner < outer || (is_rhs && inner == outer)
+            }
+            Expr::Binary(_) | Expr::Unary(_) | Expr::Between(_) | Expr::In(_) => true,
+            _ => false,
+        };
+        if wrap {
+            context.writer.write_char('(')?;
+            operand.format_writer(context)?;
+            context.writer.write_char(')')
+        } else {
+            operand.format_writer(context)
+        }
+    }
+
+    fn is_concat(&self) -> bool {
+        self.operator == ArithOperator::Concat
+    }
+
+    // mysql reads || as a logical or, chains are flattened into concat()
+    fn write_concat_args<W: Write>(
+        expr: &Expr,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        match expr {
+            Expr::Arith(inner) if inner.is_concat() => {
+                Self::write_concat_args(&inner.lhs, context)?;
+                context.writer.write_str(", ")?;
+                Self::write_concat_args(&inner.rhs, context)
+            }
+            _ => expr.format_writer(context),
+        }
+    }
+}
+
+impl FormatWriter for ArithExpr {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        if self.is_concat() && matches!(context.dialect, Dialect::MySql) {
+            context.writer.write_str("concat(")?;
+            Self::write_concat_args(&self.lhs, context)?;
+            context.writer.write_str(", ")?;
+            Self::write_concat_args(&self.rhs, context)?;
+            return context.writer.write_char(')');
+        }
+
+        self.write_operand(&self.lhs, false, context)?;
+        context.writer.write_char(' ')?;
+        self.operator.format_writer(context)?;
+        context.writer.write_char(' ')?;
+        self.write_operand(&self.rhs, true, context)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NegExpr {
+    pub(crate) expr: Expr,
+}
+
+impl FormatWriter for NegExpr {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        context.writer.write_char('-')?;
+        match self.expr {
+            Expr::Bind(_)
+            | Expr::Ident(TableRef::Ident(_))
+            | Expr::Function(_)
+            | Expr::AggregateCall(_)
+            | Expr::Cast(_)
+            | Expr::Excluded(_)
+            | Expr::Default => self.expr.format_writer(context),
+            // raw sql may start with a minus, `--` would comment out the rest
+            _ => {
+                context.writer.write_char('(')?;
+                self.expr.format_writer(context)?;
+                context.writer.write_char(')')
+            }
+        }
+    }
+}
+
+pub(crate) fn arith<L, R>(lhs: L, operator: ArithOperator, rhs: R) -> Expr
+where
+    L: Into<Expr>,
+    R: IntoRhsExpr,
+{
+    Expr::Arith(Box::new(ArithExpr {
+        lhs: lhs.into(),
+        operator,
+        rhs: rhs.into_rhs_expr(),
+    }))
+}
+
+macro_rules! impl_arith_ops {
+    ($ty:ty) => {
+        impl<R: IntoRhsExpr> std::ops::Add<R> for $ty {
+            type Output = Expr;
+
+            fn add(self, rhs: R) -> Expr {
+                arith(self, ArithOperator::Add, rhs)
+            }
+        }
+
+        impl<R: IntoRhsExpr> std::ops::Sub<R> for $ty {
+            type Output = Expr;
+
+            fn sub(self, rhs: R) -> Expr {
+                arith(self, ArithOperator::Sub, rhs)
+            }
+        }
+
+        impl<R: IntoRhsExpr> std::ops::Mul<R> for $ty {
+            type Output = Expr;
+
+            fn mul(self, rhs: R) -> Expr {
+                arith(self, ArithOperator::Mul, rhs)
+            }
+        }
+
+        impl<R: IntoRhsExpr> std::ops::Div<R> for $ty {
+            type Output = Expr;
+
+            fn div(self, rhs: R) -> Expr {
+                arith(self, ArithOperator::Div, rhs)
+            }
+        }
+
+        impl<R: IntoRhsExpr> std::ops::Rem<R> for $ty {
+            type Output = Expr;
+
+            fn rem(self, rhs: R) -> Expr {
+                arith(self, ArithOperator::Rem, rhs)
+            }
+        }
+
+        impl std::ops::Neg for $ty {
+            type Output = Expr;
+
+            fn neg(self) -> Expr {
+                Expr::Neg(Box::new(NegExpr { expr: self.into() }))
+            }
+        }
+    };
+}
+
+impl_arith_ops!(Expr);
+impl_arith_ops!(Ident);
+
+impl From<Ident> for Expr {
+    fn from(value: Ident) -> Self {
+        value.into_rhs_expr()
+    }
+}
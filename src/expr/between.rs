@@ -1,8 +1,8 @@
 use std::fmt::Write;
 
-use crate::{bind::Binds, writer::{FormatContext, FormatWriter}};
+use crate::writer::{FormatContext, FormatWriter};
 
-use super::{Expr, TakeBindings};
+use super::Expr;
 
 #[derive(Debug, Clone, Copy)]
 pub enum BetweenOperator {
@@ -18,15 +18,6 @@ pub struct BetweenCondition {
     pub(crate) operator: BetweenOperator,
 }
 
-impl TakeBindings for BetweenCondition {
-    fn take_bindings(&mut self) -> Binds {
-        let mut binds = self.lhs.take_bindings();
-        binds.append(self.low.take_bindings());
-        binds.append(self.high.take_bindings());
-        binds
-    }
-}
-
 impl FormatWriter for BetweenOperator {
     fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
         match self {
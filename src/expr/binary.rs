@@ -1,6 +1,6 @@
-use crate::{bind::Binds, dialect::Dialect, writer::FormatWriter};
+use crate::{dialect::Dialect, writer::FormatWriter};
 
-use super::{Expr, TakeBindings};
+use super::Expr;
 
 #[derive(Debug, Clone)]
 pub struct BinaryCondition {
@@ -9,24 +9,18 @@ pub struct BinaryCondition {
     pub(crate) rhs: Expr,
 }
 
-impl TakeBindings for BinaryCondition {
-    fn take_bindings(&mut self) -> Binds {
-        let mut binds = self.lhs.take_bindings();
-        binds.append(self.rhs.take_bindings());
-        binds
-    }
-}
-
 impl FormatWriter for BinaryCondition {
     fn format_writer<W: std::fmt::Write>(
         &self,
         context: &mut crate::writer::FormatContext<'_, W>,
     ) -> std::fmt::Result {
         self.lhs.format_writer(context)?;
-        if let (
-            Dialect::Postgres,
-            Operator::Like | Operator::Ilike | Operator::NotLike | Operator::NotIlike,
-        ) = (context.dialect, self.operator)
+        // an explicit cast on the lhs already picks the type
+        if !matches!(self.lhs, Expr::Cast(_))
+            && let (
+                Dialect::Postgres,
+                Operator::Like | Operator::Ilike | Operator::NotLike | Operator::NotIlike,
+            ) = (context.dialect, self.operator)
         {
             context.writer.write_str("::text")?;
         };
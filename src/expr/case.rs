@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+use crate::{
+    Builder, Error, Ident,
+    dialect::Dialect,
+    ident::IntoIdent,
+    writer::{FormatContext, FormatWriter},
+};
+
+use super::{Expr, IntoLhsExpr, IntoRhsExpr, cond::Conditions};
+
+#[derive(Debug, Clone)]
+enum CaseWhen {
+    Conditions(Conditions),
+    Value(Expr),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CaseExpr {
+    maybe_operand: Option<Expr>,
+    whens: Vec<(CaseWhen, Expr)>,
+    maybe_else: Option<Expr>,
+    alias: Option<Ident>,
+}
+
+impl CaseExpr {
+    pub(crate) fn unaliased(mut self) -> Self {
+        self.alias = None;
+        self
+    }
+
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        let values = self.maybe_operand.iter().chain(self.maybe_else.iter());
+        for value in values {
+            value.validate(dialect)?;
+        }
+        for (when, then) in &self.whens {
+            match when {
+                CaseWhen::Conditions(conditions) => conditions.validate(dialect)?,
+                CaseWhen::Value(value) => value.validate(dialect)?,
+            }
+            then.validate(dialect)?;
+        }
+        Ok(())
+    }
+}
+
+impl FormatWriter for CaseExpr {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        // a case needs at least one branch, fall back to the else value
+        if self.whens.is_empty() {
+            match self.maybe_else {
+                Some(ref otherwise) => otherwise.format_writer(context)?,
+                None => context.writer.write_str("null")?,
+            }
+            return context.write_alias(self.alias.as_ref());
+        }
+
+        context.writer.write_str("case")?;
+        if let Some(ref operand) = self.maybe_operand {
+            context.writer.write_char(' ')?;
+            operand.format_writer(context)?;
+        }
+        for (when, then) in &self.whens {
+            context.writer.write_str(" when ")?;
+            match when {
+                CaseWhen::Conditions(conditions) if conditions.is_empty() => {
+                    context.writer.write_str("1 = 1")?
+                }
+                CaseWhen::Conditions(conditions) => conditions.format_writer(context)?,
+                CaseWhen::Value(value) => value.format_writer(context)?,
+            }
+            context.writer.write_str(" then ")?;
+            then.format_writer(context)?;
+        }
+        if let Some(ref otherwise) = self.maybe_else {
+            context.writer.write_str(" else ")?;
+            otherwise.format_writer(context)?;
+        }
+        context.writer.write_str(" end")?;
+        context.write_alias(self.alias.as_ref())
+    }
+}
+
+/// A searched `case when <conditions> then ... end`, the conditions are
+/// written with the `where_*` methods of the builder passed to the closure.
+#[derive(Debug, Clone, Default)]
+pub struct Case(CaseExpr);
+
+impl Case {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn when<F, T>(mut self, conditions: F, then: T) -> Self
+    where
+        F: FnOnce(&mut Builder),
+        T: IntoRhsExpr,
+    {
+        let conditions = Builder::where_conditions(conditions);
+        self.0
+            .whens
+            .push((CaseWhen::Conditions(conditions), then.into_rhs_expr()));
+        self
+    }
+
+    pub fn otherwise<T: IntoRhsExpr>(mut self, value: T) -> Self {
+        self.0.maybe_else = Some(value.into_rhs_expr());
+        self
+    }
+
+    pub fn alias<I: IntoIdent>(mut self, alias: I) -> Self {
+        self.0.alias = Some(alias.into_ident());
+        self
+    }
+
+    pub(crate) fn into_case_expr(self) -> CaseExpr {
+        self.0
+    }
+}
+
+/// A simple `case <operand> when <value> then ... end`.
+#[derive(Debug, Clone)]
+pub struct CaseOn(CaseExpr);
+
+impl CaseOn {
+    pub fn new<O: IntoLhsExpr>(operand: O) -> Self {
+        Self(CaseExpr {
+            maybe_operand: Some(operand.into_lhs_expr()),
+            ..Default::default()
+        })
+    }
+
+    pub fn when<V, T>(mut self, value: V, then: T) -> Self
+    where
+        V: IntoRhsExpr,
+        T: IntoRhsExpr,
+    {
+        self.0
+            .whens
+            .push((CaseWhen::Value(value.into_rhs_expr()), then.into_rhs_expr()));
+        self
+    }
+
+    pub fn otherwise<T: IntoRhsExpr>(mut self, value: T) -> Self {
+        self.0.maybe_else = Some(value.into_rhs_expr());
+        self
+    }
+
+    pub fn alias<I: IntoIdent>(mut self, alias: I) -> Self {
+        self.0.alias = Some(alias.into_ident());
+        self
+    }
+
+    pub(crate) fn into_case_expr(self) -> CaseExpr {
+        self.0
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Write;
+
+use crate::{
+    dialect::Dialect,
+    writer::{FormatContext, FormatWriter},
+};
+
+use super::Expr;
+
+/// Portable type names for casts, each dialect renders its closest type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlType {
+    Text,
+    Integer,
+    BigInt,
+    Decimal(u8, u8),
+    Date,
+    Timestamp,
+    Json,
+    Uuid,
+    Boolean,
+}
+
+impl FormatWriter for SqlType {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        match context.dialect {
+            Dialect::Postgres => match self {
+                SqlType::Text => context.writer.write_str("text"),
+                SqlType::Integer => context.writer.write_str("integer"),
+                SqlType::BigInt => context.writer.write_str("bigint"),
+                SqlType::Decimal(precision, scale) => {
+                    write!(context.writer, "numeric({}, {})", precision, scale)
+                }
+                SqlType::Date => context.writer.write_str("date"),
+                SqlType::Timestamp => context.writer.write_str("timestamp"),
+                SqlType::Json => context.writer.write_str("json"),
+                SqlType::Uuid => context.writer.write_str("uuid"),
+                SqlType::Boolean => context.writer.write_str("boolean"),
+            },
+            // mysql only casts to a handful of types
+            Dialect::MySql => match self {
+                SqlType::Text => context.writer.write_str("char"),
+                SqlType::Integer | SqlType::BigInt | SqlType::Boolean => {
+                    context.writer.write_str("signed")
+                }
+                SqlType::Decimal(precision, scale) => {
+                    write!(context.writer, "decimal({}, {})", precision, scale)
+                }
+                SqlType::Date => context.writer.write_str("date"),
+                SqlType::Timestamp => context.writer.write_str("datetime"),
+                SqlType::Json => context.writer.write_str("json"),
+                SqlType::Uuid => context.writer.write_str("char(36)"),
+            },
+            // sqlite only has storage classes, dates and json are text
+            Dialect::Sqlite => match self {
+                SqlType::Integer | SqlType::BigInt | SqlType::Boolean => {
+                    context.writer.write_str("integer")
+                }
+                SqlType::Decimal(..) => context.writer.write_str("numeric"),
+                SqlType::Text
+                | SqlType::Date
+                | SqlType::Timestamp
+                | SqlType::Json
+                | SqlType::Uuid => context.writer.write_str("text"),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CastExpr {
+    pub(crate) expr: Expr,
+    pub(crate) ty: SqlType,
+}
+
+impl FormatWriter for CastExpr {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        context.writer.write_str("cast(")?;
+        self.expr.format_writer(context)?;
+        context.writer.write_str(" as ")?;
+        self.ty.format_writer(context)?;
+        context.writer.write_char(')')
+    }
+}
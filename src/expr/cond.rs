@@ -1,7 +1,7 @@
-use crate::{bind::Binds, writer::FormatWriter, Builder, Raw};
+use crate::{dialect::Dialect, ident::TableRef, writer::FormatWriter, Builder, Error, Ident, Raw};
 
 use super::{
-    Expr, TakeBindings,
+    Expr,
     between::{BetweenCondition, BetweenOperator},
     binary::{BinaryCondition, Operator},
     exists::{ExistsExpr, ExistsOperator},
@@ -44,20 +44,6 @@ pub enum ConditionKind {
     Exists(ExistsExpr),
 }
 
-impl TakeBindings for ConditionKind {
-    fn take_bindings(&mut self) -> Binds {
-        match self {
-            ConditionKind::Binary(condition) => condition.take_bindings(),
-            ConditionKind::Group(condition) => condition.take_bindings(),
-            ConditionKind::Raw(_) => Binds::None,
-            ConditionKind::Unary(condition) => condition.take_bindings(),
-            ConditionKind::Between(condition) => condition.take_bindings(),
-            ConditionKind::In(condition) => condition.take_bindings(),
-            ConditionKind::Exists(condition) => condition.take_bindings(),
-        }
-    }
-}
-
 impl FormatWriter for ConditionKind {
     fn format_writer<W: std::fmt::Write>(
         &self,
@@ -96,27 +82,9 @@ impl Condition {
     }
 }
 
-impl TakeBindings for Condition {
-    fn take_bindings(&mut self) -> Binds {
-        self.kind.take_bindings()
-    }
-}
-
 #[derive(Debug, Default, Clone)]
 pub struct Conditions(pub(crate) Vec<Condition>);
 
-impl TakeBindings for Conditions {
-    fn take_bindings(&mut self) -> Binds {
-        self.0
-            .iter_mut()
-            .map(|v| v.take_bindings())
-            .fold(Binds::None, |mut acc, next| {
-                acc.append(next);
-                acc
-            })
-    }
-}
-
 impl Conditions {
     pub fn push(&mut self, other: Condition) {
         self.0.push(other);
@@ -126,6 +94,61 @@ impl Conditions {
         self.0.is_empty()
     }
 
+    /// Whether the conditions need parentheses once joined with `and` to
+    /// other conditions, a raw condition may hold its own `or`.
+    pub(crate) fn needs_group(&self) -> bool {
+        match self.0.as_slice() {
+            [condition] => matches!(condition.kind, ConditionKind::Raw(_)),
+            _ => true,
+        }
+    }
+
+    /// The column pairs of conditions only made of `and` joined column
+    /// equalities, as in `a.id = b.id and a.kind = b.kind`.
+    pub(crate) fn column_equalities(&self) -> Option<Vec<(&Ident, &Ident)>> {
+        let mut pairs = Vec::new();
+        for condition in &self.0 {
+            let ConditionKind::Binary(ref binary) = condition.kind else {
+                return None;
+            };
+            if !matches!(condition.conjunction, Conjunction::And)
+                || !matches!(binary.operator, Operator::Eq)
+            {
+                return None;
+            }
+            match (&binary.lhs, &binary.rhs) {
+                (Expr::Ident(TableRef::Ident(lhs)), Expr::Ident(TableRef::Ident(rhs))) => {
+                    pairs.push((lhs, rhs))
+                }
+                _ => return None,
+            }
+        }
+        Some(pairs)
+    }
+
+    /// Validates the calls and subqueries of the conditions.
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        for condition in &self.0 {
+            match condition.kind {
+                ConditionKind::Group(ref group) => group.conditions.validate(dialect)?,
+                ConditionKind::In(ref inc) => inc.validate(dialect)?,
+                ConditionKind::Exists(ref exists) => exists.subquery.validate(dialect)?,
+                ConditionKind::Binary(ref binary) => {
+                    binary.lhs.validate(dialect)?;
+                    binary.rhs.validate(dialect)?;
+                }
+                ConditionKind::Unary(ref unary) => unary.lhs.validate(dialect)?,
+                ConditionKind::Between(ref between) => {
+                    between.lhs.validate(dialect)?;
+                    between.low.validate(dialect)?;
+                    between.high.validate(dialect)?;
+                }
+                ConditionKind::Raw(_) => {}
+            }
+        }
+        Ok(())
+    }
+
     pub fn push_unary(&mut self, conjunction: Conjunction, lhs: Expr, operator: UnaryOperator) {
         let cond = UnaryCondition { lhs, operator };
         let kind = ConditionKind::Unary(cond);
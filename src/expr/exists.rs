@@ -1,11 +1,9 @@
 use std::fmt::Write;
 
 use crate::{
-    bind::Binds, writer::{FormatContext, FormatWriter}, Builder, Ident
+    writer::{FormatContext, FormatWriter}, Builder, Ident
 };
 
-use super::TakeBindings;
-
 #[derive(Debug, Clone)]
 pub struct ExistsExpr {
     pub(crate) operator: ExistsOperator,
@@ -13,12 +11,6 @@ pub struct ExistsExpr {
     pub(crate) alias: Option<Ident>,
 }
 
-impl TakeBindings for ExistsExpr {
-    fn take_bindings(&mut self) -> Binds {
-        self.subquery.take_bindings()
-    }
-}
-
 impl ExistsExpr {
     pub fn new(operator: ExistsOperator, subquery: Builder, alias: Option<Ident>) -> Self {
         Self {
@@ -1,12 +1,30 @@
-use crate::{Ident, writer::FormatWriter};
+use smol_str::SmolStr;
 
-#[derive(Debug, Copy, Clone)]
+use crate::{
+    Builder, Error, Ident, Raw,
+    dialect::Dialect,
+    ident::{IntoIdent, TableRef},
+    writer::FormatWriter,
+};
+
+use super::{
+    Expr, IntoRhsExpr,
+    cond::Conditions,
+    window::{IntoWindow, Over},
+};
+
+#[derive(Debug, Clone)]
 pub enum Aggregate {
     Avg,
     Sum,
     Max,
     Min,
     Count,
+    StringAgg(SmolStr),
+    ArrayAgg,
+    JsonAgg,
+    BoolAnd,
+    BoolOr,
 }
 
 impl FormatWriter for Aggregate {
@@ -14,26 +32,145 @@ impl FormatWriter for Aggregate {
         &self,
         context: &mut crate::writer::FormatContext<'_, W>,
     ) -> std::fmt::Result {
-        match self {
-            Aggregate::Avg => context.writer.write_str("avg("),
-            Aggregate::Sum => context.writer.write_str("sum("),
-            Aggregate::Max => context.writer.write_str("max("),
-            Aggregate::Min => context.writer.write_str("min("),
-            Aggregate::Count => context.writer.write_str("count(")
-        }
+        let name = match (self, context.dialect) {
+            (Aggregate::Avg, _) => "avg(",
+            (Aggregate::Sum, _) => "sum(",
+            (Aggregate::Max, _) => "max(",
+            (Aggregate::Min, _) => "min(",
+            (Aggregate::Count, _) => "count(",
+            (Aggregate::StringAgg(_), Dialect::Postgres) => "string_agg(",
+            (Aggregate::StringAgg(_), _) => "group_concat(",
+            (Aggregate::ArrayAgg, Dialect::Postgres) => "array_agg(",
+            (Aggregate::JsonAgg, Dialect::Postgres) => "json_agg(",
+            // there are no arrays outside postgres, collect into json instead
+            (Aggregate::ArrayAgg | Aggregate::JsonAgg, Dialect::MySql) => "json_arrayagg(",
+            (Aggregate::ArrayAgg | Aggregate::JsonAgg, Dialect::Sqlite) => "json_group_array(",
+            (Aggregate::BoolAnd, Dialect::Postgres) => "bool_and(",
+            (Aggregate::BoolOr, Dialect::Postgres) => "bool_or(",
+            // booleans are plain integers on mysql and sqlite
+            (Aggregate::BoolAnd, _) => "min(",
+            (Aggregate::BoolOr, _) => "max(",
+        };
+        context.writer.write_str(name)
+    }
+}
+
+/// Anything an aggregate can run over, identifiers may carry an alias
+/// with `"column as alias"`.
+pub trait IntoAggregateArg {
+    fn into_aggregate_arg(self) -> (Expr, Option<Ident>);
+}
+
+impl<T: IntoIdent> IntoAggregateArg for T {
+    fn into_aggregate_arg(self) -> (Expr, Option<Ident>) {
+        let (column, alias) = self.into_ident().split_alias();
+        (Expr::Ident(TableRef::Ident(column)), alias)
+    }
+}
+
+impl IntoAggregateArg for Expr {
+    fn into_aggregate_arg(self) -> (Expr, Option<Ident>) {
+        (self, None)
+    }
+}
+
+impl IntoAggregateArg for Raw {
+    fn into_aggregate_arg(self) -> (Expr, Option<Ident>) {
+        (Expr::Ident(TableRef::Raw(self)), None)
+    }
+}
+
+impl IntoAggregateArg for FunctionCall {
+    fn into_aggregate_arg(self) -> (Expr, Option<Ident>) {
+        (Expr::Function(Box::new(self.unaliased())), None)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct AggregateCall {
     agg: Aggregate,
-    column: Ident,
+    arg: Expr,
+    distinct: bool,
+    maybe_filter: Option<Conditions>,
     alias: Option<Ident>,
+    maybe_over: Option<Box<Over>>,
 }
 
 impl AggregateCall {
     pub fn new(agg: Aggregate, column: Ident, alias: Option<Ident>) -> Self {
-        Self { agg, column, alias }
+        let mut call = Self::of(agg, column);
+        call.alias = alias;
+        call
+    }
+
+    /// An aggregate over any argument, see [`IntoAggregateArg`].
+    pub fn of<A: IntoAggregateArg>(agg: Aggregate, arg: A) -> Self {
+        let (arg, alias) = arg.into_aggregate_arg();
+        Self {
+            agg,
+            arg,
+            distinct: false,
+            maybe_filter: None,
+            alias,
+            maybe_over: None,
+        }
+    }
+
+    pub fn distinct(mut self) -> Self {
+        self.distinct = true;
+        self
+    }
+
+    /// Only aggregates the rows matching the conditions, postgres uses
+    /// `filter (where ...)` and the other dialects a `case when` argument.
+    pub fn filter<F>(mut self, conditions: F) -> Self
+    where
+        F: FnOnce(&mut Builder),
+    {
+        let conditions = Builder::where_conditions(conditions);
+        self.maybe_filter = (!conditions.is_empty()).then_some(conditions);
+        self
+    }
+
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        if self.distinct {
+            let reason = match (&self.agg, dialect) {
+                (Aggregate::StringAgg(separator), Dialect::Sqlite) if separator != "," => {
+                    Some("sqlite group_concat(distinct) only joins with a comma")
+                }
+                (Aggregate::ArrayAgg | Aggregate::JsonAgg, Dialect::MySql) => {
+                    Some("mysql json_arrayagg has no distinct")
+                }
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                return Err(Error::Unsupported { dialect, reason });
+            }
+        }
+        self.arg.validate(dialect)?;
+        match self.maybe_filter {
+            Some(ref filter) => filter.validate(dialect),
+            None => Ok(()),
+        }
+    }
+
+    pub fn over<W: IntoWindow>(mut self, window: W) -> Self {
+        self.maybe_over = Some(Box::new(window.into_window()));
+        self
+    }
+
+    pub fn alias<I: IntoIdent>(mut self, alias: I) -> Self {
+        self.alias = Some(alias.into_ident());
+        self
+    }
+
+    pub(crate) fn unaliased(mut self) -> Self {
+        self.alias = None;
+        self
+    }
+
+    fn is_star(&self) -> bool {
+        matches!(&self.arg, Expr::Ident(TableRef::Ident(ident)) if ident.as_str() == "*")
     }
 }
 
@@ -43,9 +180,188 @@ impl FormatWriter for AggregateCall {
         context: &mut crate::writer::FormatContext<'_, W>,
     ) -> std::fmt::Result {
         self.agg.format_writer(context)?;
-        self.column.format_writer(context)?;
+        if self.distinct {
+            context.writer.write_str("distinct ")?;
+        }
+        match self.maybe_filter {
+            Some(ref filter) if !matches!(context.dialect, Dialect::Postgres) => {
+                context.writer.write_str("case when ")?;
+                filter.format_writer(context)?;
+                context.writer.write_str(" then ")?;
+                // count(*) counts every row, count the matching ones instead
+                if self.is_star() {
+                    context.writer.write_char('1')?;
+                } else {
+                    self.arg.format_writer(context)?;
+                }
+                context.writer.write_str(" end")?;
+            }
+            _ => self.arg.format_writer(context)?,
+        }
+        if let Aggregate::StringAgg(ref separator) = self.agg
+            // the comma is the default, sqlite takes no separator with distinct
+            && !(self.distinct && separator == "," && matches!(context.dialect, Dialect::Sqlite))
+        {
+            match context.dialect {
+                Dialect::MySql => context.writer.write_str(" separator ")?,
+                Dialect::Postgres | Dialect::Sqlite => context.writer.write_str(", ")?,
+            }
+            // mysql does not accept a placeholder for the separator
+            context.write_str_literal(separator)?;
+        }
         context.writer.write_char(')')?;
+        if let Some(ref filter) = self.maybe_filter
+            && matches!(context.dialect, Dialect::Postgres)
+        {
+            context.writer.write_str(" filter (where ")?;
+            filter.format_writer(context)?;
+            context.writer.write_char(')')?;
+        }
+        if let Some(ref over) = self.maybe_over {
+            over.format_writer(context)?;
+        }
         context.write_alias(self.alias.as_ref())?;
         Ok(())
     }
 }
+
+#[derive(Debug, Clone)]
+pub enum Function {
+    Lower,
+    Upper,
+    Coalesce,
+    NullIf,
+    Length,
+    Abs,
+    Round,
+    Substring,
+    Now,
+    Named(SmolStr),
+}
+
+impl Function {
+    // the name of the function on the dialect, named functions are written
+    // as given
+    fn name(&self, dialect: Dialect) -> &str {
+        match (self, dialect) {
+            (Function::Lower, _) => "lower",
+            (Function::Upper, _) => "upper",
+            (Function::Coalesce, _) => "coalesce",
+            (Function::NullIf, _) => "nullif",
+            // mysql length counts bytes, not characters
+            (Function::Length, Dialect::MySql) => "char_length",
+            (Function::Length, _) => "length",
+            (Function::Abs, _) => "abs",
+            (Function::Round, _) => "round",
+            // sqlite only knows substring since 3.34
+            (Function::Substring, Dialect::Sqlite) => "substr",
+            (Function::Substring, _) => "substring",
+            (Function::Now, _) => "now",
+            (Function::Named(name), _) => name.as_str(),
+        }
+    }
+}
+
+impl FormatWriter for Function {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut crate::writer::FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        context.writer.write_str(self.name(context.dialect))?;
+        context.writer.write_char('(')
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionCall {
+    function: Function,
+    args: Vec<Expr>,
+    alias: Option<Ident>,
+}
+
+impl FunctionCall {
+    pub fn new(function: Function, args: Vec<Expr>) -> Self {
+        Self {
+            function,
+            args,
+            alias: None,
+        }
+    }
+
+    pub fn alias<I: IntoIdent>(mut self, alias: I) -> Self {
+        self.alias = Some(alias.into_ident());
+        self
+    }
+
+    pub(crate) fn unaliased(mut self) -> Self {
+        self.alias = None;
+        self
+    }
+
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        self.args.iter().try_for_each(|arg| arg.validate(dialect))
+    }
+}
+
+impl FormatWriter for FunctionCall {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut crate::writer::FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        // sqlite has no now(), this is the same `yyyy-mm-dd hh:mm:ss` text
+        if matches!(self.function, Function::Now) && matches!(context.dialect, Dialect::Sqlite) {
+            context.writer.write_str("datetime('now')")?;
+            return context.write_alias(self.alias.as_ref());
+        }
+        self.function.format_writer(context)?;
+        for (index, arg) in self.args.iter().enumerate() {
+            if index > 0 {
+                context.writer.write_str(", ")?;
+            }
+            arg.format_writer(context)?;
+        }
+        context.writer.write_char(')')?;
+        context.write_alias(self.alias.as_ref())
+    }
+}
+
+pub trait IntoFunctionArgs {
+    fn into_function_args(self) -> Vec<Expr>;
+}
+
+impl IntoFunctionArgs for () {
+    fn into_function_args(self) -> Vec<Expr> {
+        Vec::new()
+    }
+}
+
+impl IntoFunctionArgs for Vec<Expr> {
+    fn into_function_args(self) -> Vec<Expr> {
+        self
+    }
+}
+
+impl<T: IntoRhsExpr, const N: usize> IntoFunctionArgs for [T; N] {
+    fn into_function_args(self) -> Vec<Expr> {
+        self.into_iter().map(|arg| arg.into_rhs_expr()).collect()
+    }
+}
+
+macro_rules! impl_function_args {
+    ($($arg:ident),+) => {
+        impl<$($arg: IntoRhsExpr),+> IntoFunctionArgs for ($($arg,)+) {
+            #[allow(non_snake_case)]
+            fn into_function_args(self) -> Vec<Expr> {
+                let ($($arg,)+) = self;
+                vec![$($arg.into_rhs_expr()),+]
+            }
+        }
+    };
+}
+
+impl_function_args!(A);
+impl_function_args!(A, B);
+impl_function_args!(A, B, C);
+impl_function_args!(A, B, C, D);
+impl_function_args!(A, B, C, D, E);
+impl_function_args!(A, B, C, D, E, F);
@@ -1,18 +1,12 @@
-use crate::{bind::Binds, writer::FormatWriter};
+use crate::writer::FormatWriter;
 
-use super::{TakeBindings, cond::Conditions};
+use super::cond::Conditions;
 
 #[derive(Debug, Clone)]
 pub struct GroupCondition {
     pub(crate) conditions: Conditions,
 }
 
-impl TakeBindings for GroupCondition {
-    fn take_bindings(&mut self) -> Binds {
-        self.conditions.take_bindings()
-    }
-}
-
 impl FormatWriter for GroupCondition {
     fn format_writer<W: std::fmt::Write>(
         &self,
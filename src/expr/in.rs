@@ -1,8 +1,8 @@
 use crate::{
-    bind::Binds, writer::{self, FormatWriter}, Ident
+    bind::Bind, dialect::Dialect, writer::{self, FormatWriter}, Error, Ident
 };
 
-use super::{Expr, TakeBindings, list::InList};
+use super::{Expr, list::InList};
 
 #[derive(Debug, Clone)]
 pub struct InExpr {
@@ -21,13 +21,13 @@ impl InExpr {
             alias,
         }
     }
-}
 
-impl TakeBindings for InExpr {
-    fn take_bindings(&mut self) -> Binds {
-        let mut binds = self.lhs.take_bindings();
-        binds.append(self.rhs.take_bindings());
-        binds
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        self.lhs.validate(dialect)?;
+        if let InList::Subquery(ref sub) = self.rhs {
+            sub.validate(dialect)?;
+        }
+        Ok(())
     }
 }
 
@@ -42,6 +42,29 @@ impl FormatWriter for InExpr {
         &self,
         context: &mut writer::FormatContext<'_, W>,
     ) -> std::fmt::Result {
+        // `in ()` is a syntax error, nothing is in an empty list
+        if self.rhs.is_empty() {
+            match self.operator {
+                InOperator::In => context.writer.write_str("1 = 0")?,
+                InOperator::NotIn => context.writer.write_str("1 = 1")?,
+            }
+            return context.write_alias(self.alias.as_ref());
+        }
+
+        if let InList::Array(ref array) = self.rhs
+            && matches!(context.dialect, Dialect::Postgres)
+            && !context.expand_arrays
+        {
+            self.lhs.format_writer(context)?;
+            match self.operator {
+                InOperator::In => context.writer.write_str(" = any(")?,
+                InOperator::NotIn => context.writer.write_str(" <> all(")?,
+            }
+            context.write_bind(&Bind::Array(Box::new(array.clone())))?;
+            context.writer.write_char(')')?;
+            return context.write_alias(self.alias.as_ref());
+        }
+
         self.lhs.format_writer(context)?;
         context.writer.write_char(' ')?;
         self.operator.format_writer(context)?;
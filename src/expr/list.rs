@@ -1,24 +1,23 @@
-use crate::{bind::{Binds, IntoBinds}, expr::TakeBindings, writer::FormatWriter, Builder};
+use crate::{bind::{ArrayBind, Binds, IntoBinds}, writer::FormatWriter, Builder};
 
 #[derive(Debug, Clone)]
 pub enum InList {
     Binds(Binds),
     Subquery(Box<Builder>),
-}
-
-impl TakeBindings for InList {
-    fn take_bindings(&mut self) -> Binds {
-        match self {
-            InList::Binds(array) => array.take_bindings(),
-            InList::Subquery(builder) => builder.take_bindings(),
-        }
-    }
+    // a single array bind on postgres, expanded on the other dialects
+    Array(ArrayBind),
 }
 
 pub trait IntoInList {
     fn into_in_list(self) -> InList;
 }
 
+impl IntoInList for InList {
+    fn into_in_list(self) -> InList {
+        self
+    }
+}
+
 impl IntoInList for Builder {
     fn into_in_list(self) -> InList {
         InList::Subquery(Box::new(self))
@@ -34,6 +33,16 @@ where
     }
 }
 
+impl InList {
+    pub(crate) fn is_empty(&self) -> bool {
+        match self {
+            InList::Binds(binds) => binds.is_empty(),
+            InList::Array(array) => array.is_empty(),
+            InList::Subquery(_) => false,
+        }
+    }
+}
+
 impl FormatWriter for InList {
     fn format_writer<W: std::fmt::Write>(
         &self,
@@ -42,6 +51,7 @@ impl FormatWriter for InList {
         match self {
             InList::Binds(array) => array.format_writer(context),
             InList::Subquery(builder) => builder.format_writer(context),
+            InList::Array(array) => array.elements().format_writer(context),
         }
     }
 }
@@ -1,29 +1,38 @@
-use crate::{bind::Binds, col::IntoTable, ident::TableRef, writer::FormatWriter, Raw};
+use crate::{col::IntoTable, ident::TableRef, writer::FormatWriter, Raw};
 
+pub(crate) mod arith;
 pub(crate) mod between;
+pub mod case;
+pub mod cast;
 pub(crate) mod binary;
 pub(crate) mod cond;
 pub(crate) mod exists;
-pub(crate) mod fncall;
+pub mod fncall;
 pub(crate) mod group;
 pub(crate) mod r#in;
 pub(crate) mod list;
 pub(crate) mod order;
 pub(crate) mod sub;
 pub(crate) mod unary;
+pub mod window;
 
+use arith::{ArithExpr, ArithOperator, NegExpr};
 use between::{BetweenCondition, BetweenOperator};
+use case::{Case, CaseExpr, CaseOn};
+use cast::{CastExpr, SqlType};
 use binary::BinaryCondition;
 pub use cond::Conjunction;
 use exists::ExistsExpr;
-use fncall::AggregateCall;
+use fncall::{AggregateCall, FunctionCall};
 use r#in::InExpr;
 use qraft_derive::variant;
 use unary::{UnaryCondition, UnaryOperator};
+use window::WindowCall;
 
 use crate::{
-    Builder, Ident, IntoBind,
-    bind::{Array, Bind},
+    Builder, Error, Ident, IntoBind,
+    bind::Bind,
+    dialect::Dialect,
     expr::binary::Operator,
 };
 
@@ -35,10 +44,20 @@ pub enum Expr {
     Subquery(Box<Builder>),
     Exists(ExistsExpr),
     In(Box<InExpr>),
-    AggregateCall(AggregateCall),
+    AggregateCall(Box<AggregateCall>),
+    Function(Box<FunctionCall>),
+    Window(Box<WindowCall>),
     Binary(Box<BinaryCondition>),
     Unary(Box<UnaryCondition>),
     Between(Box<BetweenCondition>),
+    Arith(Box<ArithExpr>),
+    Neg(Box<NegExpr>),
+    Case(Box<CaseExpr>),
+    Cast(Box<CastExpr>),
+    // the value proposed for insertion in an upsert
+    Excluded(Ident),
+    // the column default of an insert, left out of updates
+    Default,
 }
 
 impl Expr {
@@ -83,24 +102,47 @@ impl Expr {
         };
         Expr::Between(Box::new(btw))
     }
-}
 
-pub(crate) trait TakeBindings {
-    fn take_bindings(&mut self) -> Binds;
-}
+    pub fn cast(self, ty: SqlType) -> Self {
+        Expr::Cast(Box::new(CastExpr { expr: self, ty }))
+    }
 
-impl TakeBindings for Expr {
-    fn take_bindings(&mut self) -> Binds {
+    /// String concatenation, `||` on postgres and sqlite, `concat()` on mysql.
+    pub fn concat<R>(self, other: R) -> Self
+    where
+        R: IntoRhsExpr,
+    {
+        arith::arith(self, ArithOperator::Concat, other)
+    }
+
+    /// Validates the calls and subqueries nested in the expression.
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
         match self {
-            Expr::Bind(bind) => Array::One(std::mem::replace(bind, Bind::Consumed)),
-            Expr::Ident(ident) => ident.take_bindings(),
-            Expr::Subquery(builder) => builder.take_bindings(),
-            Expr::Exists(condition) => condition.take_bindings(),
-            Expr::In(condition) => condition.take_bindings(),
-            Expr::AggregateCall(_) => Binds::None,
-            Expr::Binary(condition) => condition.take_bindings(),
-            Expr::Unary(condition) => condition.take_bindings(),
-            Expr::Between(condition) => condition.take_bindings(),
+            Expr::Ident(TableRef::AliasSub(sub)) => sub.inner.validate(dialect),
+            Expr::Subquery(builder) => builder.validate(dialect),
+            Expr::Exists(exists) => exists.subquery.validate(dialect),
+            Expr::In(inc) => inc.validate(dialect),
+            Expr::AggregateCall(aggregate) => aggregate.validate(dialect),
+            Expr::Function(function) => function.validate(dialect),
+            Expr::Window(window) => window.validate(dialect),
+            Expr::Binary(binary) => {
+                binary.lhs.validate(dialect)?;
+                binary.rhs.validate(dialect)
+            }
+            Expr::Unary(unary) => unary.lhs.validate(dialect),
+            Expr::Between(between) => {
+                between.lhs.validate(dialect)?;
+                between.low.validate(dialect)?;
+                between.high.validate(dialect)
+            }
+            Expr::Arith(arith) => {
+                arith.lhs.validate(dialect)?;
+                arith.rhs.validate(dialect)
+            }
+            Expr::Neg(neg) => neg.expr.validate(dialect),
+            Expr::Case(case) => case.validate(dialect),
+            Expr::Cast(cast) => cast.expr.validate(dialect),
+            Expr::Bind(_) | Expr::Ident(_) | Expr::Excluded(_) | Expr::Default => Ok(()),
         }
     }
 }
@@ -111,7 +153,7 @@ impl FormatWriter for Expr {
         context: &mut crate::writer::FormatContext<'_, W>,
     ) -> std::fmt::Result {
         match self {
-            Expr::Bind(_) => context.write_placeholder(),
+            Expr::Bind(bind) => context.write_bind(bind),
             Expr::Ident(ident) => ident.format_writer(context),
             Expr::Subquery(builder) => {
                 context.writer.write_char('(')?;
@@ -121,9 +163,28 @@ impl FormatWriter for Expr {
             Expr::Exists(condition) => condition.format_writer(context),
             Expr::In(condition) => condition.format_writer(context),
             Expr::AggregateCall(aggregate) => aggregate.format_writer(context),
+            Expr::Function(function) => function.format_writer(context),
+            Expr::Window(window) => window.format_writer(context),
             Expr::Binary(condition) => condition.format_writer(context),
             Expr::Unary(condition) => condition.format_writer(context),
-            Expr::Between(condition) => condition.format_writer(context)
+            Expr::Between(condition) => condition.format_writer(context),
+            Expr::Arith(arith) => arith.format_writer(context),
+            Expr::Neg(neg) => neg.format_writer(context),
+            Expr::Case(case) => case.format_writer(context),
+            Expr::Cast(cast) => cast.format_writer(context),
+            Expr::Excluded(column) => match context.dialect {
+                Dialect::MySql => {
+                    context.writer.write_str("values(")?;
+                    column.format_writer(context)?;
+                    context.writer.write_char(')')
+                }
+                Dialect::Postgres | Dialect::Sqlite => {
+                    context.write_ident("excluded")?;
+                    context.writer.write_char('.')?;
+                    column.format_writer(context)
+                }
+            },
+            Expr::Default => context.writer.write_str("default"),
         }
     }
 }
@@ -238,3 +299,52 @@ impl IntoLhsExpr for Builder {
         Expr::Subquery(Box::new(self))
     }
 }
+
+// aliases only make sense in projections
+impl IntoLhsExpr for AggregateCall {
+    fn into_lhs_expr(self) -> Expr {
+        Expr::AggregateCall(Box::new(self.unaliased()))
+    }
+}
+
+impl IntoLhsExpr for FunctionCall {
+    fn into_lhs_expr(self) -> Expr {
+        Expr::Function(Box::new(self.unaliased()))
+    }
+}
+
+impl IntoRhsExpr for FunctionCall {
+    fn into_rhs_expr(self) -> Expr {
+        Expr::Function(Box::new(self.unaliased()))
+    }
+}
+
+impl IntoLhsExpr for Case {
+    fn into_lhs_expr(self) -> Expr {
+        Expr::Case(Box::new(self.into_case_expr().unaliased()))
+    }
+}
+
+impl IntoRhsExpr for Case {
+    fn into_rhs_expr(self) -> Expr {
+        Expr::Case(Box::new(self.into_case_expr().unaliased()))
+    }
+}
+
+impl IntoLhsExpr for CaseOn {
+    fn into_lhs_expr(self) -> Expr {
+        Expr::Case(Box::new(self.into_case_expr().unaliased()))
+    }
+}
+
+impl IntoRhsExpr for CaseOn {
+    fn into_rhs_expr(self) -> Expr {
+        Expr::Case(Box::new(self.into_case_expr().unaliased()))
+    }
+}
+
+impl IntoLhsExpr for WindowCall {
+    fn into_lhs_expr(self) -> Expr {
+        Expr::Window(Box::new(self.unaliased()))
+    }
+}
@@ -1,10 +1,10 @@
 use std::fmt;
 
 use crate::{
-    bind::{Array, Binds}, dialect::Dialect, writer::{self, FormatWriter}, Raw
+    bind::Array, dialect::Dialect, ident::TableRef, writer::{self, FormatWriter}, Ident, Raw
 };
 
-use super::{Expr, TakeBindings};
+use super::Expr;
 
 #[derive(Debug, Clone)]
 pub enum OrderExpr {
@@ -13,29 +13,8 @@ pub enum OrderExpr {
     Random,
 }
 
-impl TakeBindings for OrderExpr {
-    fn take_bindings(&mut self) -> Binds {
-        match self {
-            OrderExpr::Column(table_ref, _) => table_ref.take_bindings(),
-            OrderExpr::Raw(_) => Binds::None,
-            OrderExpr::Random => Binds::None,
-        }
-    }
-}
-
 pub type OrderProjections = Array<OrderExpr>;
 
-impl TakeBindings for OrderProjections {
-    fn take_bindings(&mut self) -> Binds {
-        self.iter_mut()
-            .map(|v| v.take_bindings())
-            .fold(Binds::None, |mut acc, next| {
-                acc.append(next);
-                acc
-            })
-    }
-}
-
 impl FormatWriter for OrderExpr {
     fn format_writer<W: fmt::Write>(
         &self,
@@ -61,12 +40,6 @@ pub struct Order {
     projections: OrderProjections,
 }
 
-impl TakeBindings for Order {
-    fn take_bindings(&mut self) -> Binds {
-        self.projections.take_bindings()
-    }
-}
-
 impl FormatWriter for Order {
     fn format_writer<W: fmt::Write>(
         &self,
@@ -99,6 +72,27 @@ impl Order {
     pub fn push_random(&mut self) {
         self.projections.push(OrderExpr::Random);
     }
+
+    /// The order of a compound select, which only sees the output columns.
+    /// Qualified columns lose their table, `None` when an expression could
+    /// still reference one.
+    pub(crate) fn by_output_names(&self) -> Option<Order> {
+        let mut order = Order::default();
+        for proj in self.projections.iter() {
+            let proj = match proj {
+                OrderExpr::Column(Expr::Ident(TableRef::Ident(ident)), ordering) => {
+                    let name = ident.as_str();
+                    let column = name.rsplit_once('.').map_or(name, |(_, column)| column);
+                    let column = Expr::Ident(TableRef::Ident(Ident::new(column)));
+                    OrderExpr::Column(column, *ordering)
+                }
+                OrderExpr::Column(..) => return None,
+                other => other.clone(),
+            };
+            order.projections.push(proj);
+        }
+        Some(order)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -1,23 +1,15 @@
 use smol_str::SmolStr;
 
 use crate::{
-    bind::Binds, col::AliasSub, ident::IntoIdent, writer::{self, FormatWriter}, Builder
+    col::AliasSub, ident::IntoIdent, writer::{self, FormatWriter}, Builder
 };
 
-use super::TakeBindings;
-
 #[derive(Debug, Clone)]
 pub struct AliasSubFn {
     keyword: SmolStr,
     inner: AliasSub,
 }
 
-impl TakeBindings for AliasSubFn {
-    fn take_bindings(&mut self) -> Binds {
-        self.inner.take_bindings()
-    }
-}
-
 impl AliasSubFn {
     pub fn new<I, T>(keyword: I, inner: Builder, alias: T) -> Self
     where
@@ -1,8 +1,8 @@
 use std::fmt::Write;
 
-use crate::{bind::Binds, writer::{FormatContext, FormatWriter}};
+use crate::writer::{FormatContext, FormatWriter};
 
-use super::{Expr, TakeBindings};
+use super::Expr;
 
 #[derive(Debug, Clone, Copy)]
 pub enum UnaryOperator {
@@ -18,12 +18,6 @@ pub struct UnaryCondition {
     pub(crate) operator: UnaryOperator,
 }
 
-impl TakeBindings for UnaryCondition {
-    fn take_bindings(&mut self) -> Binds {
-        self.lhs.take_bindings()
-    }
-}
-
 impl FormatWriter for UnaryCondition {
     fn format_writer<W: std::fmt::Write>(
         &self,
@@ -0,0 +1,325 @@
+use std::fmt::Write;
+
+use crate::{
+    Error, Ident,
+    col::IntoColumns,
+    dialect::Dialect,
+    ident::IntoIdent,
+    insert::Columns,
+    writer::{FormatContext, FormatWriter},
+};
+
+use super::{
+    Expr, IntoLhsExpr,
+    order::{Order, Ordering},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum FrameUnits {
+    Rows,
+    Range,
+}
+
+impl FormatWriter for FrameUnits {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        match self {
+            FrameUnits::Rows => context.writer.write_str("rows"),
+            FrameUnits::Range => context.writer.write_str("range"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing,
+}
+
+impl FormatWriter for FrameBound {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        match self {
+            FrameBound::UnboundedPreceding => context.writer.write_str("unbounded preceding"),
+            FrameBound::Preceding(n) => write!(context.writer, "{} preceding", n),
+            FrameBound::CurrentRow => context.writer.write_str("current row"),
+            FrameBound::Following(n) => write!(context.writer, "{} following", n),
+            FrameBound::UnboundedFollowing => context.writer.write_str("unbounded following"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    units: FrameUnits,
+    start: FrameBound,
+    end: FrameBound,
+}
+
+impl FormatWriter for Frame {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        self.units.format_writer(context)?;
+        context.writer.write_str(" between ")?;
+        self.start.format_writer(context)?;
+        context.writer.write_str(" and ")?;
+        self.end.format_writer(context)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Window {
+    maybe_partition: Option<Columns>,
+    maybe_order: Option<Order>,
+    maybe_frame: Option<Frame>,
+}
+
+impl Window {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn partition_by<C: IntoColumns>(&mut self, columns: C) -> &mut Self {
+        let target = self.maybe_partition.get_or_insert_default();
+        target.append(columns.into_columns());
+        self
+    }
+
+    pub fn order_by<I: IntoLhsExpr>(&mut self, column: I, ordering: Ordering) -> &mut Self {
+        let o = self.maybe_order.get_or_insert_default();
+        o.push_expr(column.into_lhs_expr(), ordering);
+        self
+    }
+
+    pub fn order_by_asc<I: IntoLhsExpr>(&mut self, column: I) -> &mut Self {
+        self.order_by(column, Ordering::Asc)
+    }
+
+    pub fn order_by_desc<I: IntoLhsExpr>(&mut self, column: I) -> &mut Self {
+        self.order_by(column, Ordering::Desc)
+    }
+
+    pub fn rows_between(&mut self, start: FrameBound, end: FrameBound) -> &mut Self {
+        self.maybe_frame = Some(Frame {
+            units: FrameUnits::Rows,
+            start,
+            end,
+        });
+        self
+    }
+
+    pub fn range_between(&mut self, start: FrameBound, end: FrameBound) -> &mut Self {
+        self.maybe_frame = Some(Frame {
+            units: FrameUnits::Range,
+            start,
+            end,
+        });
+        self
+    }
+
+    pub fn build(&mut self) -> Self {
+        std::mem::take(self)
+    }
+}
+
+impl FormatWriter for Window {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        let mut space = false;
+        if let Some(ref partition) = self.maybe_partition
+            && !partition.is_empty()
+        {
+            context.writer.write_str("partition by ")?;
+            partition.format_writer(context)?;
+            space = true;
+        }
+        if let Some(ref order) = self.maybe_order
+            && !order.is_empty()
+        {
+            if space {
+                context.writer.write_char(' ')?;
+            }
+            context.writer.write_str("order by ")?;
+            order.format_writer(context)?;
+            space = true;
+        }
+        if let Some(ref frame) = self.maybe_frame {
+            if space {
+                context.writer.write_char(' ')?;
+            }
+            frame.format_writer(context)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Over {
+    Named(Ident),
+    Window(Box<Window>),
+}
+
+impl Default for Over {
+    fn default() -> Self {
+        Over::Window(Box::default())
+    }
+}
+
+impl FormatWriter for Over {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        context.writer.write_str(" over ")?;
+        match self {
+            Over::Named(name) => name.format_writer(context),
+            Over::Window(window) => {
+                context.writer.write_char('(')?;
+                window.format_writer(context)?;
+                context.writer.write_char(')')
+            }
+        }
+    }
+}
+
+pub trait IntoWindow {
+    fn into_window(self) -> Over;
+}
+
+impl IntoWindow for Window {
+    fn into_window(self) -> Over {
+        Over::Window(Box::new(self))
+    }
+}
+
+impl IntoWindow for &str {
+    fn into_window(self) -> Over {
+        Over::Named(self.into_ident())
+    }
+}
+
+impl IntoWindow for String {
+    fn into_window(self) -> Over {
+        Over::Named(self.into_ident())
+    }
+}
+
+impl IntoWindow for Ident {
+    fn into_window(self) -> Over {
+        Over::Named(self)
+    }
+}
+
+impl<F> IntoWindow for F
+where
+    F: FnOnce(&mut Window),
+{
+    fn into_window(self) -> Over {
+        let mut window = Window::new();
+        self(&mut window);
+        Over::Window(Box::new(window))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NamedWindow {
+    name: Ident,
+    window: Window,
+}
+
+impl NamedWindow {
+    pub fn new(name: Ident, window: Window) -> Self {
+        Self { name, window }
+    }
+}
+
+impl FormatWriter for NamedWindow {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        self.name.format_writer(context)?;
+        context.writer.write_str(" as (")?;
+        self.window.format_writer(context)?;
+        context.writer.write_char(')')
+    }
+}
+
+pub type NamedWindows = Vec<NamedWindow>;
+
+#[derive(Debug, Clone, Copy)]
+pub enum WindowFunction {
+    RowNumber,
+    Rank,
+    DenseRank,
+    PercentRank,
+    CumeDist,
+    Ntile,
+    Lag,
+    Lead,
+    FirstValue,
+    LastValue,
+}
+
+impl FormatWriter for WindowFunction {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        match self {
+            WindowFunction::RowNumber => context.writer.write_str("row_number("),
+            WindowFunction::Rank => context.writer.write_str("rank("),
+            WindowFunction::DenseRank => context.writer.write_str("dense_rank("),
+            WindowFunction::PercentRank => context.writer.write_str("percent_rank("),
+            WindowFunction::CumeDist => context.writer.write_str("cume_dist("),
+            WindowFunction::Ntile => context.writer.write_str("ntile("),
+            WindowFunction::Lag => context.writer.write_str("lag("),
+            WindowFunction::Lead => context.writer.write_str("lead("),
+            WindowFunction::FirstValue => context.writer.write_str("first_value("),
+            WindowFunction::LastValue => context.writer.write_str("last_value("),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WindowCall {
+    function: WindowFunction,
+    args: Vec<Expr>,
+    over: Over,
+    alias: Option<Ident>,
+}
+
+impl WindowCall {
+    pub fn new(function: WindowFunction, args: Vec<Expr>) -> Self {
+        Self {
+            function,
+            args,
+            over: Over::default(),
+            alias: None,
+        }
+    }
+
+    pub fn over<W: IntoWindow>(mut self, window: W) -> Self {
+        self.over = window.into_window();
+        self
+    }
+
+    pub(crate) fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        self.args.iter().try_for_each(|arg| arg.validate(dialect))
+    }
+
+    pub fn alias<I: IntoIdent>(mut self, alias: I) -> Self {
+        self.alias = Some(alias.into_ident());
+        self
+    }
+
+    pub(crate) fn unaliased(mut self) -> Self {
+        self.alias = None;
+        self
+    }
+}
+
+impl FormatWriter for WindowCall {
+    fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result {
+        self.function.format_writer(context)?;
+        for (index, arg) in self.args.iter().enumerate() {
+            if index > 0 {
+                context.writer.write_str(", ")?;
+            }
+            arg.format_writer(context)?;
+        }
+        context.writer.write_char(')')?;
+        self.over.format_writer(context)?;
+        context.write_alias(self.alias.as_ref())
+    }
+}
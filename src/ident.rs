@@ -3,9 +3,7 @@ use std::{borrow::Cow, fmt::Display, sync::Arc};
 use smol_str::{format_smolstr, SmolStr};
 
 use crate::{
-    bind::{Array, Binds},
     col::AliasSub,
-    expr::TakeBindings,
     raw::Raw,
     writer::{self, FormatWriter},
 };
@@ -41,16 +39,6 @@ impl Default for TableRef {
     }
 }
 
-impl TakeBindings for TableRef {
-    fn take_bindings(&mut self) -> Binds {
-        match self {
-            TableRef::Ident(_) => Array::None,
-            TableRef::Raw(_) => Array::None,
-            TableRef::AliasSub(builder) => builder.take_bindings(),
-        }
-    }
-}
-
 impl TableRef {
     pub fn ident_static(value: &'static str) -> Self {
         Self::Ident(Ident::new_static(value))
@@ -1,9 +1,23 @@
 use std::collections::HashSet;
 
+use crate::Builder;
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
+use crate::dialect::HasExecutorDialect;
 use crate::{
-    bind::{Array, Binds}, col::{IntoColumns, IntoTable}, dialect::{Dialect, HasDialect}, expr::TakeBindings, ident::{IntoIdent, RawOrIdent, TableRef}, row::{IntoRow, Row}, writer::{FormatContext, FormatWriter}, Ident
+    Error, Ident,
+    bind::{Array, Binds},
+    col::{IntoColumns, IntoTable},
+    dialect::{Dialect, HasDialect},
+    expr::{Expr, cond::Conditions},
+    ident::{IntoIdent, RawOrIdent, TableRef},
+    row::{IntoRow, Row},
+    writer::{FormatContext, FormatWriter},
 };
-use crate::Builder;
 
 pub type Columns = Array<RawOrIdent>;
 
@@ -16,15 +30,20 @@ impl IntoTable for RawOrIdent {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InsertBuilder {
     table: Ident,
     columns: Columns,
-    binds: Binds,
     rows: Vec<Row>,
     maybe_conflict_cols: Option<Array<RawOrIdent>>,
     maybe_sets: Option<Array<RawOrIdent>>,
+    maybe_assignments: Option<Row>,
+    maybe_update_where: Option<Conditions>,
+    maybe_conflict_constraint: Option<Ident>,
+    maybe_conflict_where: Option<Conditions>,
+    do_nothing: bool,
     maybe_select: Option<Box<Builder>>,
+    maybe_returning: Option<Columns>,
 }
 
 impl FormatWriter for Array<Ident> {
@@ -47,10 +66,15 @@ impl InsertBuilder {
         Self {
             table: table.into_ident(),
             columns: Columns::None,
-            binds: Binds::None,
             maybe_conflict_cols: None,
             maybe_sets: None,
+            maybe_assignments: None,
+            maybe_update_where: None,
+            maybe_conflict_constraint: None,
+            maybe_conflict_where: None,
+            do_nothing: false,
             maybe_select: None,
+            maybe_returning: None,
             rows: Vec::new(),
         }
     }
@@ -61,9 +85,7 @@ impl InsertBuilder {
     }
 
     pub fn row<R: IntoRow>(&mut self, row: R) -> &mut Self {
-        let mut row = row.into_row();
-        self.binds.append(row.take_bindings());
-        self.rows.push(row);
+        self.rows.push(row.into_row());
         self
     }
 
@@ -94,6 +116,70 @@ impl InsertBuilder {
         self
     }
 
+    /// Upserts with expressions, use [`excluded`] for the value that was
+    /// proposed. Postgres needs the existing column qualified by the table,
+    /// as in `users.hits`.
+    ///
+    /// [`excluded`]: crate::excluded
+    pub fn upsert_with<C, R>(&mut self, conflicted: C, assignments: R) -> &mut Self
+    where
+        C: IntoColumns,
+        R: IntoRow,
+    {
+        let target = self.maybe_conflict_cols.get_or_insert_default();
+        target.append(conflicted.into_columns());
+        let target = self.maybe_assignments.get_or_insert_default();
+        target.values.extend(assignments.into_row().values);
+        self
+    }
+
+    /// Only updates the conflicting rows matching the predicate, postgres
+    /// and sqlite only.
+    pub fn upsert_where<F>(&mut self, predicate: F) -> &mut Self
+    where
+        F: FnOnce(&mut Builder),
+    {
+        let conditions = Builder::where_conditions(predicate);
+        let target = self.maybe_update_where.get_or_insert_default();
+        for condition in conditions.0 {
+            target.push(condition);
+        }
+        self
+    }
+
+    /// Skips the rows that conflict, `on conflict do nothing` on postgres
+    /// and sqlite, `insert ignore` on mysql. Without columns any unique
+    /// constraint counts as a conflict.
+    pub fn on_conflict_do_nothing<C: IntoColumns>(&mut self, conflicted: C) -> &mut Self {
+        let target = self.maybe_conflict_cols.get_or_insert_default();
+        target.append(conflicted.into_columns());
+        self.do_nothing = true;
+        self
+    }
+
+    /// Targets a named constraint instead of columns, postgres only. Follow
+    /// it with `upsert` or `on_conflict_do_nothing` given no columns. Mysql
+    /// and sqlite can not match a single constraint, `try_*` and the
+    /// executors return [`Error::Unsupported`] there.
+    pub fn on_conflict_on_constraint<N: IntoIdent>(&mut self, name: N) -> &mut Self {
+        self.maybe_conflict_constraint = Some(name.into_ident());
+        self
+    }
+
+    /// The predicate of a partial unique index, written after the conflict
+    /// columns so postgres and sqlite can infer the index.
+    pub fn on_conflict_where<F>(&mut self, predicate: F) -> &mut Self
+    where
+        F: FnOnce(&mut Builder),
+    {
+        let conditions = Builder::where_conditions(predicate);
+        let target = self.maybe_conflict_where.get_or_insert_default();
+        for condition in conditions.0 {
+            target.push(condition);
+        }
+        self
+    }
+
     pub fn select<C, F>(&mut self, cols: C, select: F) -> &mut Self
     where
         C: IntoColumns,
@@ -103,52 +189,498 @@ impl InsertBuilder {
         self.columns.append(cols.into_columns());
         let mut builder = Builder::default();
         select(&mut builder);
-        self.binds = Binds::None;
-        let select_binds = builder.take_bindings();
-        self.binds.append(select_binds);
         self.maybe_select = Some(Box::new(builder));
 
         self
     }
 
+    /// Rendered on postgres and sqlite (3.35+) only, mysql has no returning
+    /// clause so use `insert_get_id` which falls back to `last_insert_id`.
+    pub fn returning<C: IntoColumns>(&mut self, columns: C) -> &mut Self {
+        self.maybe_returning = Some(columns.into_columns());
+        self
+    }
+
+    // the insert itself is left as is, a later execute renders the same sql
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    fn or_returning(&self, columns: &'static str) -> std::borrow::Cow<'_, Self> {
+        if self.maybe_returning.is_some() {
+            return std::borrow::Cow::Borrowed(self);
+        }
+        let mut insert = self.clone();
+        insert.returning(columns);
+        std::borrow::Cow::Owned(insert)
+    }
+
     pub fn build(&mut self) -> Self {
         Self {
             table: std::mem::take(&mut self.table),
             columns: self.columns.take(),
-            binds: self.binds.take(),
             rows: std::mem::take(&mut self.rows),
             maybe_conflict_cols: self.maybe_conflict_cols.take(),
             maybe_sets: self.maybe_sets.take(),
+            maybe_assignments: self.maybe_assignments.take(),
+            maybe_update_where: self.maybe_update_where.take(),
+            maybe_conflict_constraint: self.maybe_conflict_constraint.take(),
+            maybe_conflict_where: self.maybe_conflict_where.take(),
+            do_nothing: std::mem::take(&mut self.do_nothing),
             maybe_select: self.maybe_select.take(),
+            maybe_returning: self.maybe_returning.take(),
         }
     }
 
+    /// Renders the sql alone, binds are left as placeholders.
+    ///
+    /// # Panics
+    ///
+    /// Past the bind limit of the dialect, [`InsertBuilder::try_to_sql`] returns
+    /// [`Error::TooManyBinds`] instead.
     pub fn to_sql<Database: HasDialect>(&self) -> String {
+        self.to_sql_for(Database::DIALECT)
+    }
+
+    /// Renders the insert for a dialect only known at runtime.
+    pub fn to_sql_for(&self, dialect: Dialect) -> String {
         let size_hint = 64;
         let mut str = String::with_capacity(size_hint);
-        let mut context = FormatContext::new(&mut str, Database::DIALECT);
-        self.format_writer(&mut context)
-            .expect("should not fail on a string writer");
+        let mut context = FormatContext::new(&mut str, dialect);
+        if let Err(err) = self.format_writer(&mut context) {
+            panic!("{}", context.render_error(err));
+        }
         str
     }
 
-    #[cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+    /// Renders the sql along with its bindings, gathered in placeholder
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Past the bind limit of the dialect or on a `u64` bind above `i64::MAX`
+    /// on postgres and sqlite, [`InsertBuilder::try_build_sql`] returns
+    /// [`Error::TooManyBinds`] or [`Error::BindOutOfRange`] instead.
+    pub fn build_sql<Database: HasDialect>(&self) -> (String, Binds) {
+        self.build_sql_for(Database::DIALECT)
+    }
+
+    pub fn build_sql_for(&self, dialect: Dialect) -> (String, Binds) {
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::with_binds(&mut str, dialect);
+        if let Err(err) = self.format_writer(&mut context) {
+            panic!("{}", context.render_error(err));
+        }
+        let binds = context.take_binds();
+        (str, binds)
+    }
+
+    /// Like [`InsertBuilder::to_sql`] but rejects an insert without rows or
+    /// an upsert that would leave a dangling conflict clause.
+    pub fn try_to_sql<Database: HasDialect>(&self) -> Result<String, Error> {
+        self.try_to_sql_for(Database::DIALECT)
+    }
+
+    pub fn try_to_sql_for(&self, dialect: Dialect) -> Result<String, Error> {
+        self.validate(dialect)?;
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::new(&mut str, dialect);
+        self.format_writer(&mut context)
+            .map_err(|err| context.render_error(err))?;
+        Ok(str)
+    }
+
+    pub fn try_build_sql<Database: HasDialect>(&self) -> Result<(String, Binds), Error> {
+        self.try_build_sql_for(Database::DIALECT)
+    }
+
+    pub fn try_build_sql_for(&self, dialect: Dialect) -> Result<(String, Binds), Error> {
+        self.try_build_sql_with(dialect, false)
+    }
+
+    // the any driver has no arrays, they go one placeholder per element
+    pub(crate) fn try_build_sql_with(
+        &self,
+        dialect: Dialect,
+        expand_arrays: bool,
+    ) -> Result<(String, Binds), Error> {
+        self.validate(dialect)?;
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::with_binds(&mut str, dialect);
+        context.expand_arrays = expand_arrays;
+        self.format_writer(&mut context)
+            .map_err(|err| context.render_error(err))?;
+        let binds = context.take_binds();
+        Ok((str, binds))
+    }
+
+    /// Splits the rows into as few inserts as the dialect bind limit allows.
+    /// Every chunk keeps the same columns, conflict clause and returning, an
+    /// insert from a select is returned as is.
+    pub fn chunks_for(&self, dialect: Dialect) -> Result<Vec<InsertBuilder>, Error> {
+        if self.maybe_select.is_some() {
+            return Ok(vec![self.clone()]);
+        }
+
+        let template = InsertBuilder {
+            table: self.table.clone(),
+            columns: Columns::Many(self.header(dialect)),
+            rows: Vec::new(),
+            maybe_conflict_cols: self.maybe_conflict_cols.clone(),
+            maybe_sets: self.maybe_sets.clone(),
+            maybe_assignments: self.maybe_assignments.clone(),
+            maybe_update_where: self.maybe_update_where.clone(),
+            maybe_conflict_constraint: self.maybe_conflict_constraint.clone(),
+            maybe_conflict_where: self.maybe_conflict_where.clone(),
+            do_nothing: self.do_nothing,
+            maybe_select: None,
+            maybe_returning: self.maybe_returning.clone(),
+        };
+        // binds outside of the values are repeated in every chunk
+        let overhead = count_binds(&template, dialect)?;
+        let limit = dialect.max_binds();
+
+        let mut chunks = Vec::new();
+        let mut current = template.clone();
+        let mut used = overhead;
+        for row in &self.rows {
+            let mut binds = 0;
+            for expr in row.values.values() {
+                binds += count_binds(expr, dialect)?;
+            }
+            if overhead + binds > limit {
+                return Err(Error::TooManyBinds { dialect, limit });
+            }
+            if used + binds > limit {
+                chunks.push(std::mem::replace(&mut current, template.clone()));
+                used = overhead;
+            }
+            current.rows.push(row.clone());
+            used += binds;
+        }
+        chunks.push(current);
+        Ok(chunks)
+    }
+
+    fn header(&self, dialect: Dialect) -> Vec<RawOrIdent> {
+        if !self.columns.is_empty() {
+            return self.columns.iter().cloned().collect();
+        }
+
+        let mut seen = HashSet::new();
+        let mut cols = Vec::new();
+        for row in &self.rows {
+            for ident in row.values.keys() {
+                if seen.insert(ident.clone()) {
+                    cols.push(RawOrIdent::Ident(ident.clone()));
+                }
+            }
+        }
+        // sqlite has no `default` in values, a column defaulted in every
+        // row is left out so the table default applies
+        if matches!(dialect, Dialect::Sqlite) {
+            cols.retain(|col| match col {
+                RawOrIdent::Ident(ident) => self.rows.iter().any(|row| {
+                    row.values
+                        .get(ident)
+                        .is_some_and(|expr| !matches!(expr, Expr::Default))
+                }),
+                RawOrIdent::Raw(_) => true,
+            });
+        }
+        cols
+    }
+
+    // a row missing a column renders `default`, which sqlite rejects
+    fn has_defaults(&self, dialect: Dialect) -> bool {
+        let header = self.header(dialect);
+        header.is_empty()
+            || self.rows.iter().any(|row| {
+                header.iter().any(|col| match col {
+                    RawOrIdent::Ident(ident) => row
+                        .values
+                        .get(ident)
+                        .is_none_or(|expr| matches!(expr, Expr::Default)),
+                    RawOrIdent::Raw(_) => true,
+                })
+            })
+    }
+
+    fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        match self.maybe_select {
+            Some(ref select) => select.validate(dialect)?,
+            None if self.rows.is_empty() => return Err(Error::EmptyInsert),
+            None => {}
+        }
+
+        for conditions in [&self.maybe_conflict_where, &self.maybe_update_where]
+            .into_iter()
+            .flatten()
+        {
+            conditions.validate(dialect)?;
+        }
+        if let Some(reason) = self.unsupported_conflict(dialect) {
+            return Err(Error::Unsupported { dialect, reason });
+        }
+        if self.maybe_select.is_none()
+            && matches!(dialect, Dialect::Sqlite)
+            && self.has_defaults(dialect)
+        {
+            return Err(Error::Unsupported {
+                dialect,
+                reason: "sqlite has no default in values, leave the column out of every row",
+            });
+        }
+
+        let is_upsert = self.maybe_conflict_cols.is_some()
+            || self.maybe_sets.is_some()
+            || self.maybe_assignments.is_some();
+        let has_conflicts = self
+            .maybe_conflict_cols
+            .as_ref()
+            .is_some_and(|c| !c.is_empty())
+            || self.maybe_conflict_constraint.is_some();
+        let has_sets = self.has_sets();
+        let incomplete = match dialect {
+            _ if self.do_nothing => false,
+            // mysql finds the conflicting unique key on its own
+            Dialect::MySql => is_upsert && !has_sets,
+            Dialect::Postgres | Dialect::Sqlite => has_conflicts != has_sets,
+        };
+        if incomplete {
+            return Err(Error::IncompleteUpsert);
+        }
+        Ok(())
+    }
+
+    fn has_sets(&self) -> bool {
+        self.maybe_sets.as_ref().is_some_and(|s| !s.is_empty())
+            || self
+                .maybe_assignments
+                .as_ref()
+                .is_some_and(|row| !row.values.is_empty())
+    }
+
+    fn unsupported_conflict(&self, dialect: Dialect) -> Option<&'static str> {
+        if self.maybe_update_where.is_some() && matches!(dialect, Dialect::MySql) {
+            return Some("mysql has no where on duplicate key update");
+        }
+        let has_columns = self
+            .maybe_conflict_cols
+            .as_ref()
+            .is_some_and(|c| !c.is_empty());
+        if self.maybe_conflict_constraint.is_some() {
+            match dialect {
+                Dialect::Sqlite => return Some("sqlite has no on conflict on constraint"),
+                // it would upsert on any unique key, not only the constraint
+                Dialect::MySql => return Some("mysql has no conflict target"),
+                Dialect::Postgres => {}
+            }
+            if has_columns {
+                return Some("on conflict takes either columns or a constraint");
+            }
+        }
+        if self.maybe_conflict_where.is_some() {
+            // mysql can not pick the unique index, it would match any of them
+            if matches!(dialect, Dialect::MySql) {
+                return Some("mysql has no conflict target");
+            }
+            if !has_columns {
+                return Some("the conflict predicate needs conflict columns");
+            }
+        }
+        None
+    }
+
+    // ` on conflict` followed by its target, postgres and sqlite only
+    fn format_conflict_target<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        context.writer.write_str(" on conflict")?;
+        if let Some(ref constraint) = self.maybe_conflict_constraint {
+            context.writer.write_str(" on constraint ")?;
+            constraint.format_writer(context)?;
+        } else if let Some(ref conflicts) = self.maybe_conflict_cols
+            && !conflicts.is_empty()
+        {
+            context.writer.write_str(" (")?;
+            conflicts.format_writer(context)?;
+            context.writer.write_char(')')?;
+            if let Some(ref conditions) = self.maybe_conflict_where
+                && !conditions.is_empty()
+            {
+                context.writer.write_str(" where ")?;
+                conditions.format_writer(context)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
     pub async fn execute<DB, E>(
-        &mut self,
+        &self,
         executor: E,
-    ) -> Result<<DB as sqlx::Database>::QueryResult, sqlx::Error>
+    ) -> Result<<DB as sqlx::Database>::QueryResult, Error>
     where
-        DB: sqlx::Database + HasDialect,
-        E: for<'c> sqlx::Executor<'c, Database = DB>,
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
         Binds: for<'c> sqlx::IntoArguments<'c, DB>,
         <DB as sqlx::Database>::QueryResult: crate::dialect::HasRowsAffected,
     {
-        let bindings = self.binds.take();
-        let sql = self.to_sql::<DB>();
-        sqlx::query_with::<_, _>(&sql, bindings)
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_with::<_, _>(&sql, bindings)
             .execute(executor)
-            .await
+            .await?;
+        Ok(result)
+    }
+
+    /// Executes every chunk of [`InsertBuilder::chunks_for`] and sums the
+    /// affected rows. The chunks already sent stay inserted when a later one
+    /// fails, [`InsertBuilder::insert_many`] runs them in one transaction.
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn execute_chunked<DB, E>(&self, executor: E) -> Result<usize, Error>
+    where
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect + Copy,
+        Binds: for<'c> sqlx::IntoArguments<'c, DB>,
+        <DB as sqlx::Database>::QueryResult: crate::dialect::HasRowsAffected,
+    {
+        use crate::dialect::HasRowsAffected;
+
+        let mut total = 0;
+        for chunk in self.chunks_for(executor.dialect()?)? {
+            let result = chunk.execute::<DB, E>(executor).await?;
+            total += result.rows_affected();
+        }
+        Ok(total)
+    }
+
+    /// Like [`InsertBuilder::execute_chunked`] inside a single transaction,
+    /// either every row is inserted or none.
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn insert_many<'p, DB>(&self, pool: &'p sqlx::Pool<DB>) -> Result<usize, Error>
+    where
+        DB: sqlx::Database,
+        &'p sqlx::Pool<DB>: HasExecutorDialect,
+        for<'c> &'c mut <DB as sqlx::Database>::Connection: sqlx::Executor<'c, Database = DB>,
+        Binds: for<'c> sqlx::IntoArguments<'c, DB>,
+        <DB as sqlx::Database>::QueryResult: crate::dialect::HasRowsAffected,
+    {
+        use crate::dialect::HasRowsAffected;
+
+        let dialect = pool.dialect()?;
+        let chunks = self.chunks_for(dialect)?;
+        let mut tx = pool.begin().await?;
+        let mut total = 0;
+        for chunk in chunks {
+            let (sql, bindings) = chunk.try_build_sql_with(dialect, pool.expands_arrays())?;
+            let result = sqlx::query_with::<_, _>(&sql, bindings)
+                .execute(&mut *tx)
+                .await?;
+            total += result.rows_affected();
+        }
+        tx.commit().await?;
+        Ok(total)
     }
+
+    /// Returns the generated id of the inserted row, through `returning` on
+    /// postgres and sqlite (defaults to the `id` column) and through
+    /// `last_insert_id()` on mysql.
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn insert_get_id<DB, T, E>(&self, executor: E) -> Result<T, Error>
+    where
+        DB: sqlx::Database,
+        (T,): for<'r> sqlx::FromRow<'r, DB::Row>,
+        T: TryFrom<u64> + Send + Unpin,
+        <T as TryFrom<u64>>::Error: std::error::Error + Send + Sync + 'static,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
+        Binds: for<'c> sqlx::IntoArguments<'c, DB>,
+        <DB as sqlx::Database>::QueryResult:
+            crate::dialect::HasRowsAffected + crate::dialect::HasLastInsertId,
+    {
+        use crate::dialect::HasLastInsertId;
+
+        let dialect = executor.dialect()?;
+        if matches!(dialect, Dialect::MySql) {
+            let result = self.execute::<DB, E>(executor).await?;
+            let id = result.last_insert_id().ok_or(sqlx::Error::RowNotFound)?;
+            return T::try_from(id).map_err(|err| sqlx::Error::Decode(Box::new(err)).into());
+        }
+
+        let (sql, bindings) = self
+            .or_returning("id")
+            .try_build_sql_with(dialect, executor.expands_arrays())?;
+        let id = sqlx::query_scalar_with::<_, T, _>(&sql, bindings)
+            .fetch_one(executor)
+            .await?;
+        Ok(id)
+    }
+
+    /// Fetches the inserted rows, defaults to `returning *`. Not available on
+    /// mysql.
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn returning_all<DB, T, E>(&self, executor: E) -> Result<Vec<T>, Error>
+    where
+        DB: sqlx::Database,
+        T: for<'r> sqlx::FromRow<'r, DB::Row> + Send + Unpin,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
+        Binds: for<'c> sqlx::IntoArguments<'c, DB>,
+    {
+        let dialect = executor.dialect()?;
+        if matches!(dialect, Dialect::MySql) {
+            return Err(crate::builder::returning_unsupported());
+        }
+
+        let (sql, bindings) = self
+            .or_returning("*")
+            .try_build_sql_with(dialect, executor.expands_arrays())?;
+        let rows = sqlx::query_as_with::<_, T, _>(&sql, bindings)
+            .fetch_all(executor)
+            .await?;
+        Ok(rows)
+    }
+}
+
+// renders into a throwaway string only to count the placeholders
+fn count_binds<T: FormatWriter>(value: &T, dialect: Dialect) -> Result<usize, Error> {
+    let mut sink = String::new();
+    let mut context = FormatContext::new(&mut sink, dialect);
+    value
+        .format_writer(&mut context)
+        .map_err(|err| context.render_error(err))?;
+    Ok(context.placeholder as usize)
 }
 
 impl FormatWriter for InsertBuilder {
@@ -157,24 +689,15 @@ impl FormatWriter for InsertBuilder {
         context: &mut FormatContext<'_, W>,
     ) -> std::fmt::Result {
         // sanity check
-        context.writer.write_str("insert into ")?;
+        if self.do_nothing && matches!(context.dialect, Dialect::MySql) {
+            context.writer.write_str("insert ignore into ")?;
+        } else {
+            context.writer.write_str("insert into ")?;
+        }
         self.table.format_writer(context)?;
         context.writer.write_str(" (")?;
 
-        let header: Vec<RawOrIdent> = if !self.columns.is_empty() {
-            self.columns.iter().cloned().collect()
-        } else {
-            let mut seen = HashSet::new();
-            let mut cols = Vec::new();
-            for row in &self.rows {
-                for ident in row.values.keys() {
-                    if seen.insert(ident.clone()) {
-                        cols.push(RawOrIdent::Ident(ident.clone()));
-                    }
-                }
-            }
-            cols
-        };
+        let header = self.header(context.dialect);
 
         for (index, col) in header.iter().enumerate() {
             if index > 0 {
@@ -190,10 +713,14 @@ impl FormatWriter for InsertBuilder {
             context.writer.write_str("values ")?;
             // print the rows
             for (ri, row) in self.rows.iter().enumerate() {
-                if ri > 0 { context.writer.write_str(", ")?; }
+                if ri > 0 {
+                    context.writer.write_str(", ")?;
+                }
                 context.writer.write_char('(')?;
                 for (ci, col) in header.iter().enumerate() {
-                    if ci > 0 { context.writer.write_str(", ")?; }
+                    if ci > 0 {
+                        context.writer.write_str(", ")?;
+                    }
                     match col {
                         RawOrIdent::Ident(ident) => {
                             if let Some(expr) = row.values.get(ident) {
@@ -210,49 +737,79 @@ impl FormatWriter for InsertBuilder {
                 context.writer.write_char(')')?;
             }
         }
-        if let Some(ref conflicts) = self.maybe_conflict_cols {
-            if !conflicts.is_empty()
-                && matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite)
-            {
-                context.writer.write_str(" on conflict (")?;
-                conflicts.format_writer(context)?;
-                context.writer.write_char(')')?;
-            } else if matches!(context.dialect, Dialect::MySql) {
+        if self.do_nothing {
+            // mysql marks the whole statement with `insert ignore` instead
+            if matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite) {
+                self.format_conflict_target(context)?;
+                context.writer.write_str(" do nothing")?;
+            }
+        } else if matches!(context.dialect, Dialect::MySql) {
+            if self.maybe_conflict_cols.is_some() {
                 context.writer.write_str(" on duplicate key update ")?;
             }
+        } else if self.maybe_conflict_constraint.is_some()
+            || self
+                .maybe_conflict_cols
+                .as_ref()
+                .is_some_and(|c| !c.is_empty())
+        {
+            self.format_conflict_target(context)?;
         }
-        if let Some(ref sets) = self.maybe_sets {
-            if !sets.is_empty() && matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite) {
+        if !self.do_nothing && self.has_sets() {
+            if matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite) {
                 context.writer.write_str(" do update set ")?;
-                for (index, set) in sets.iter().enumerate() {
-                    if index > 0 {
-                        context.writer.write_str(", ")?;
-                    }
+            }
+            let mut count = 0;
+            for set in self.maybe_sets.iter().flat_map(|sets| sets.iter()) {
+                if count > 0 {
+                    context.writer.write_str(", ")?;
+                }
+                set.format_writer(context)?;
+                if matches!(context.dialect, Dialect::MySql) {
+                    context.writer.write_str(" = values(")?;
                     set.format_writer(context)?;
+                    context.writer.write_char(')')?;
+                } else {
                     context.writer.write_str(" = ")?;
                     let col_name = set.table_name();
                     let ident = Ident::new(smol_str::format_smolstr!("excluded.{}", col_name));
                     ident.format_writer(context)?;
                 }
-            } else if matches!(context.dialect, Dialect::MySql) {
-                for (index, set) in sets.iter().enumerate() {
-                    if index > 0 {
-                        context.writer.write_str(", ")?;
-                    }
-                    set.format_writer(context)?;
-                    context.writer.write_str(" = values(")?;
-                    set.format_writer(context)?;
-                    context.writer.write_char(')')?;
+                count += 1;
+            }
+            for (column, expr) in self.maybe_assignments.iter().flat_map(|row| &row.values) {
+                if count > 0 {
+                    context.writer.write_str(", ")?;
                 }
+                column.format_writer(context)?;
+                context.writer.write_str(" = ")?;
+                expr.format_writer(context)?;
+                count += 1;
+            }
+            if let Some(ref conditions) = self.maybe_update_where
+                && !conditions.is_empty()
+                && matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite)
+            {
+                context.writer.write_str(" where ")?;
+                conditions.format_writer(context)?;
             }
         }
+        if let Some(ref returning) = self.maybe_returning
+            && matches!(context.dialect, Dialect::Postgres | Dialect::Sqlite)
+        {
+            context.writer.write_str(" returning ")?;
+            returning.format_writer(context)?;
+        }
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{dialect::{MySql, Postgres, Sqlite}, lit};
+    use crate::{
+        dialect::{MySql, Postgres, Sqlite},
+        lit,
+    };
 
     use super::*;
 
@@ -279,6 +836,293 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_upsert_with_expressions() {
+        let insert = InsertBuilder::insert_into("pages")
+            .row(|row: &mut Row| {
+                row.field("path", "/").field("hits", 1);
+            })
+            .upsert_with(["path"], |row: &mut Row| {
+                row.field(
+                    "hits",
+                    crate::column("pages.hits") + crate::excluded("hits"),
+                )
+                .field("seen", 2);
+            })
+            .upsert_where(|b| {
+                b.where_column(crate::excluded("updated_at"), '>', "pages.updated_at");
+            })
+            .build();
+        assert_eq!(
+            r#"insert into "pages" ("path", "hits") values ($1, $2) on conflict ("path") do update set "hits" = "pages"."hits" + "excluded"."hits", "seen" = $3 where "excluded"."updated_at" > "pages"."updated_at""#,
+            insert.try_to_sql::<Postgres>().unwrap()
+        );
+        assert_eq!(insert.build_sql::<Sqlite>().1.len(), 3);
+        assert!(matches!(
+            insert.try_to_sql::<MySql>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let insert = InsertBuilder::insert_into("pages")
+            .row(|row: &mut Row| {
+                row.field("path", "/").field("hits", 1);
+            })
+            .upsert(["path"], ["path"])
+            .upsert_with(Vec::<&str>::new(), |row: &mut Row| {
+                row.field("hits", crate::column("hits") + crate::excluded("hits"));
+            })
+            .build();
+        assert_eq!(
+            "insert into `pages` (`path`, `hits`) values (?, ?) on duplicate key update `path` = values(`path`), `hits` = `hits` + values(`hits`)",
+            insert.try_to_sql::<MySql>().unwrap()
+        );
+        assert_eq!(
+            r#"insert into "pages" ("path", "hits") values (?1, ?2) on conflict ("path") do update set "path" = "excluded"."path", "hits" = "hits" + "excluded"."hits""#,
+            insert.try_to_sql::<Sqlite>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_on_conflict_do_nothing() {
+        let insert = InsertBuilder::insert_into("users")
+            .row(|row: &mut Row| {
+                row.field("email", "dany@ovior.ca");
+            })
+            .on_conflict_do_nothing(["email"])
+            .on_conflict_where(|b| {
+                b.where_null("deleted_at");
+            })
+            .build();
+        assert_eq!(
+            r#"insert into "users" ("email") values ($1) on conflict ("email") where "deleted_at" is null do nothing"#,
+            insert.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            r#"insert into "users" ("email") values (?1) on conflict ("email") where "deleted_at" is null do nothing"#,
+            insert.to_sql::<Sqlite>()
+        );
+        assert!(matches!(
+            insert.try_to_sql::<MySql>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let insert = InsertBuilder::insert_into("users")
+            .row(|row: &mut Row| {
+                row.field("email", "dany@ovior.ca");
+            })
+            .on_conflict_do_nothing(Vec::<&str>::new())
+            .build();
+        assert_eq!(
+            r#"insert into "users" ("email") values ($1) on conflict do nothing"#,
+            insert.try_to_sql::<Postgres>().unwrap()
+        );
+        assert_eq!(
+            "insert ignore into `users` (`email`) values (?)",
+            insert.try_to_sql::<MySql>().unwrap()
+        );
+
+        let insert = InsertBuilder::insert_into("users")
+            .row(|row: &mut Row| {
+                row.field("email", "dany@ovior.ca").field("name", "dany");
+            })
+            .on_conflict_on_constraint("users_email_key")
+            .upsert(Vec::<&str>::new(), ["name"])
+            .build();
+        assert_eq!(
+            r#"insert into "users" ("email", "name") values ($1, $2) on conflict on constraint "users_email_key" do update set "name" = "excluded"."name""#,
+            insert.try_to_sql::<Postgres>().unwrap()
+        );
+        // mysql would upsert on any unique key
+        assert!(matches!(
+            insert.try_to_sql::<MySql>(),
+            Err(Error::Unsupported { .. })
+        ));
+        assert!(matches!(
+            insert.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut insert = InsertBuilder::insert_into("users");
+        insert
+            .rows((0..40_000).map(|i| {
+                move |row: &mut Row| {
+                    row.field("id", i)
+                        .field("name", "ovior")
+                        .field("bio", crate::raw("1"));
+                }
+            }))
+            .upsert(["id"], ["name"]);
+
+        let chunks = insert.chunks_for(Dialect::Postgres).unwrap();
+        assert_eq!(2, chunks.len());
+        assert!(matches!(
+            insert.try_build_sql::<Postgres>(),
+            Err(Error::TooManyBinds { limit: 65535, .. })
+        ));
+
+        // two binds per row, the literal is not a bind
+        let chunks = insert.chunks_for(Dialect::Sqlite).unwrap();
+        assert_eq!(3, chunks.len());
+        assert_eq!(16_383, chunks[0].rows.len());
+        let (sql, binds) = chunks[2].try_build_sql::<Sqlite>().unwrap();
+        assert!(sql.starts_with(r#"insert into "users" ("id", "name", "bio") values (?1, ?2, 1)"#));
+        assert!(sql.ends_with(r#"on conflict ("id") do update set "name" = "excluded"."name""#));
+        assert_eq!(2 * (40_000 - 2 * 16_383), binds.len());
+
+        let mut insert = InsertBuilder::insert_into("users");
+        insert.row(|row: &mut Row| {
+            row.field(
+                "ids",
+                crate::raw("?").bind((0..40_000).collect::<Vec<i32>>()),
+            );
+        });
+        assert!(matches!(
+            insert.chunks_for(Dialect::Sqlite),
+            Err(Error::TooManyBinds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_try_to_sql() {
+        let insert = InsertBuilder::insert_into("users");
+        assert!(matches!(
+            insert.try_to_sql::<Postgres>(),
+            Err(Error::EmptyInsert)
+        ));
+
+        let insert = InsertBuilder::insert_into("users")
+            .row(|row: &mut Row| {
+                row.field("username", "ovior");
+            })
+            .upsert(["username"], Vec::<&str>::new())
+            .build();
+        assert!(matches!(
+            insert.try_to_sql::<MySql>(),
+            Err(Error::IncompleteUpsert)
+        ));
+        assert!(matches!(
+            insert.try_build_sql::<Postgres>(),
+            Err(Error::IncompleteUpsert)
+        ));
+
+        let insert = InsertBuilder::insert_into("users")
+            .row(|row: &mut Row| {
+                row.field("username", "ovior");
+            })
+            .upsert(Vec::<&str>::new(), ["username"])
+            .build();
+        assert!(insert.try_to_sql::<MySql>().is_ok());
+        assert!(insert.try_to_sql::<Sqlite>().is_err());
+    }
+
+    #[test]
+    fn test_insert_returning() {
+        let insert = InsertBuilder::insert_into("users")
+            .row(|row: &mut Row| {
+                row.field("username", "ovior");
+            })
+            .returning(["id", "created_at"])
+            .build();
+
+        assert_eq!(
+            r#"insert into "users" ("username") values ($1) returning "id", "created_at""#,
+            insert.to_sql::<Postgres>()
+        );
+        assert_eq!(
+            r#"insert into "users" ("username") values (?1) returning "id", "created_at""#,
+            insert.to_sql::<Sqlite>()
+        );
+        assert_eq!(
+            r#"insert into `users` (`username`) values (?)"#,
+            insert.to_sql::<MySql>()
+        );
+    }
+
+    #[allow(dead_code)]
+    #[derive(crate::IntoRow)]
+    #[qraft(rename_all = "snake_case")]
+    struct NewUser {
+        #[qraft(default)]
+        id: i64,
+        user_name: String,
+        #[qraft(rename = "mail")]
+        email: Option<String>,
+        #[qraft(skip_if_none)]
+        bio: Option<String>,
+        #[qraft(default)]
+        role: Option<String>,
+        #[qraft(skip)]
+        password: String,
+    }
+
+    #[test]
+    fn test_derive_into_row() {
+        let users = [
+            NewUser {
+                id: 0,
+                user_name: "ovior".into(),
+                email: None,
+                bio: None,
+                role: Some("admin".into()),
+                password: "secret".into(),
+            },
+            NewUser {
+                id: 0,
+                user_name: "dany".into(),
+                email: Some("dany@ovior.ca".into()),
+                bio: Some("hello".into()),
+                role: None,
+                password: "secret".into(),
+            },
+        ];
+        let insert = InsertBuilder::insert_into("users").rows(users).build();
+        assert_eq!(
+            r#"insert into "users" ("id", "user_name", "mail", "role", "bio") values (default, $1, $2, $3, default), (default, $4, $5, default, $6)"#,
+            insert.to_sql::<Postgres>()
+        );
+        // the mixed defaults of `role` and `bio` can not be left out
+        assert!(matches!(
+            insert.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let insert = InsertBuilder::insert_into("users")
+            .row(NewUser {
+                id: 0,
+                user_name: "ovior".into(),
+                email: None,
+                bio: None,
+                role: None,
+                password: "secret".into(),
+            })
+            .build();
+        assert_eq!(
+            r#"insert into "users" ("user_name", "mail") values (?1, ?2)"#,
+            insert.try_to_sql::<Sqlite>().unwrap()
+        );
+
+        let mut builder = Builder::table("users");
+        builder.where_eq("id", 1).update_query(
+            Dialect::Postgres,
+            NewUser {
+                id: 1,
+                user_name: "ovior".into(),
+                email: None,
+                bio: None,
+                role: None,
+                password: "secret".into(),
+            }
+            .into_row(),
+        );
+        assert_eq!(
+            r#"update "users" set "user_name" = $1, "mail" = $2 where "id" = $3"#,
+            builder.to_sql::<Postgres>()
+        );
+    }
+
     #[test]
     fn insert_builder() {
         let insert = Builder::insert_into("jobs")
@@ -312,7 +1156,9 @@ mod tests {
                 row.field("username", "ovior").field("name", "ovior");
             })
             .row(|row: &mut Row| {
-                row.field("username", "ovior").field("name", "ovior").field("created_at", 1);
+                row.field("username", "ovior")
+                    .field("name", "ovior")
+                    .field("created_at", 1);
             })
             .build();
 
@@ -1,8 +1,8 @@
 use qraft_derive::{or_variant, variant};
 
 use crate::{
-    bind::{Binds, IntoBinds}, builder::QueryKind, col::{IntoColumns, IntoTable}, expr::{
-        between::BetweenOperator, binary::Operator, cond::Conditions, exists::ExistsOperator, r#in::InOperator, list::IntoInList, unary::UnaryOperator, Conjunction, Expr, IntoLhsExpr, IntoOperator, IntoRhsExpr, TakeBindings
+    bind::IntoBinds, builder::QueryKind, col::{IntoColumns, IntoTable}, expr::{
+        between::BetweenOperator, binary::Operator, cond::Conditions, exists::ExistsOperator, r#in::InOperator, list::IntoInList, unary::UnaryOperator, Conjunction, Expr, IntoLhsExpr, IntoOperator, IntoRhsExpr
     }, ident::TableRef, insert::Columns, raw::IntoRaw, writer::FormatWriter, Builder
 };
 
@@ -11,7 +11,9 @@ pub enum JoinType {
     Inner,
     Left,
     Right,
+    Full,
     Cross,
+    Natural,
 }
 
 impl FormatWriter for JoinType {
@@ -23,7 +25,9 @@ impl FormatWriter for JoinType {
             JoinType::Inner => context.writer.write_str("inner join"),
             JoinType::Left => context.writer.write_str("left join"),
             JoinType::Right => context.writer.write_str("right join"),
+            JoinType::Full => context.writer.write_str("full outer join"),
             JoinType::Cross => context.writer.write_str("cross join"),
+            JoinType::Natural => context.writer.write_str("natural join"),
         }
     }
 }
@@ -34,8 +38,8 @@ pub struct JoinClause {
     ty: JoinType,
     maybe_table: Option<TableRef>,
     conditions: Conditions,
-    binds: Binds,
     maybe_using: Option<Columns>,
+    lateral: bool,
 }
 
 impl Default for JoinClause {
@@ -45,18 +49,12 @@ impl Default for JoinClause {
             ty: JoinType::Inner,
             maybe_table: None,
             conditions: Conditions::default(),
-            binds: Binds::None,
             maybe_using: None,
+            lateral: false,
         }
     }
 }
 
-impl TakeBindings for JoinClause {
-    fn take_bindings(&mut self) -> Binds {
-        self.binds.take_bindings()
-    }
-}
-
 pub type Joins = Vec<JoinClause>;
 
 // todo: maybe create a where clause to match this and prevent the kind ?
@@ -67,11 +65,49 @@ impl JoinClause {
             maybe_table: Some(table),
             conditions: Conditions::default(),
             kind: QueryKind::Join,
-            binds: Binds::None,
             maybe_using: None,
+            lateral: false,
         }
     }
 
+    pub(crate) fn lateral(mut self) -> Self {
+        self.lateral = true;
+        self
+    }
+
+    pub(crate) fn is_lateral(&self) -> bool {
+        self.lateral
+    }
+
+    pub(crate) fn join_type(&self) -> JoinType {
+        self.ty
+    }
+
+    pub(crate) fn with_join_type(&self, ty: JoinType) -> Self {
+        Self { ty, ..self.clone() }
+    }
+
+    // the same conditions joining another table
+    pub(crate) fn with_table(&self, ty: JoinType, table: TableRef) -> Self {
+        Self {
+            ty,
+            maybe_table: Some(table),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn has_using(&self) -> bool {
+        self.maybe_using.is_some()
+    }
+
+    pub(crate) fn conditions(&self) -> &Conditions {
+        &self.conditions
+    }
+
+    pub(crate) fn table(&self) -> Option<&TableRef> {
+        self.maybe_table.as_ref()
+    }
+
     pub fn using<C>(&mut self, columns: C) -> &mut Self
     where
         C: IntoColumns, // subqueries are not allowed !
@@ -87,10 +123,8 @@ impl JoinClause {
         O: IntoOperator,
         CC: IntoLhsExpr,
     {
-        let mut lhs = column.into_lhs_expr();
-        let mut rhs = other_column.into_lhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = column.into_lhs_expr();
+        let rhs = other_column.into_lhs_expr();
         self.conditions
             .push_binary(Conjunction::And, lhs, rhs, operator.into_operator());
         self
@@ -103,10 +137,8 @@ impl JoinClause {
         O: IntoOperator,
         V: IntoRhsExpr,
     {
-        let mut lhs = column.into_lhs_expr();
-        let mut rhs = value.into_rhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = column.into_lhs_expr();
+        let rhs = value.into_rhs_expr();
         self.conditions
             .push_binary(Conjunction::And, lhs, rhs, operator.into_operator());
         self
@@ -122,7 +154,6 @@ impl JoinClause {
             ..Default::default()
         };
         sub(&mut inner);
-        self.binds.append(inner.take_bindings());
         self.conditions
             .push_group(Conjunction::And, inner.conditions);
         self
@@ -138,7 +169,6 @@ impl JoinClause {
             ..Default::default()
         };
         sub(&mut inner);
-        self.binds.append(inner.take_bindings());
         self.conditions
             .push_group(Conjunction::And, inner.conditions);
         self
@@ -150,9 +180,7 @@ impl JoinClause {
         R: IntoRaw,
         B: IntoBinds,
     {
-        let raw = raw.into_raw();
-        let binds = binds.into_binds();
-        self.binds.append(binds);
+        let raw = raw.into_raw().bind(binds);
         self.conditions.push_raw(Conjunction::And, raw);
         self
     }
@@ -163,10 +191,8 @@ impl JoinClause {
         C: IntoLhsExpr,
         V: IntoRhsExpr,
     {
-        let mut lhs = column.into_lhs_expr();
-        let mut rhs = value.into_rhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = column.into_lhs_expr();
+        let rhs = value.into_rhs_expr();
         self.conditions
             .push_binary(Conjunction::And, lhs, rhs, Operator::Eq);
         self
@@ -177,8 +203,7 @@ impl JoinClause {
     where
         C: IntoLhsExpr,
     {
-        let mut lhs = column.into_lhs_expr();
-        self.binds.append(lhs.take_bindings());
+        let lhs = column.into_lhs_expr();
         self.conditions
             .push_unary(Conjunction::And, lhs, UnaryOperator::Null);
         self
@@ -191,12 +216,9 @@ impl JoinClause {
         L: IntoRhsExpr,
         H: IntoRhsExpr,
     {
-        let mut lhs = lhs.into_lhs_expr();
-        let mut low = low.into_rhs_expr();
-        let mut high = high.into_rhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(low.take_bindings());
-        self.binds.append(high.take_bindings());
+        let lhs = lhs.into_lhs_expr();
+        let low = low.into_rhs_expr();
+        let high = high.into_rhs_expr();
         self.conditions
             .push_between(Conjunction::And, lhs, low, high, BetweenOperator::Between);
         self
@@ -209,12 +231,9 @@ impl JoinClause {
         L: IntoLhsExpr,
         H: IntoLhsExpr,
     {
-        let mut lhs = lhs.into_lhs_expr();
-        let mut low = low.into_lhs_expr();
-        let mut high = high.into_lhs_expr();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(low.take_bindings());
-        self.binds.append(high.take_bindings());
+        let lhs = lhs.into_lhs_expr();
+        let low = low.into_lhs_expr();
+        let high = high.into_lhs_expr();
         self.conditions
             .push_between(Conjunction::And, lhs, low, high, BetweenOperator::Between);
         self
@@ -227,7 +246,6 @@ impl JoinClause {
     {
         let mut inner = Builder::default();
         sub(&mut inner);
-        self.binds.append(inner.take_bindings());
         self.conditions
             .push_exists(Conjunction::And, inner, ExistsOperator::Exists);
         self
@@ -239,10 +257,8 @@ impl JoinClause {
         L: IntoLhsExpr,
         R: IntoInList,
     {
-        let mut lhs = lhs.into_lhs_expr();
-        let mut rhs = rhs.into_in_list();
-        self.binds.append(lhs.take_bindings());
-        self.binds.append(rhs.take_bindings());
+        let lhs = lhs.into_lhs_expr();
+        let rhs = rhs.into_in_list();
         self.conditions
             .push_in(Conjunction::And, lhs, rhs, InOperator::In);
         self
@@ -306,10 +322,8 @@ impl JoinClause {
     ) -> &mut Self {
         let closure = |builder: &mut Self| {
             for proj in projections {
-                let mut lhs = proj.into_table().into_lhs_expr();
-                let mut rhs = value.clone();
-                builder.binds.append(lhs.take_bindings());
-                builder.binds.append(rhs.take_bindings());
+                let lhs = proj.into_table().into_lhs_expr();
+                let rhs = value.clone();
                 builder.conditions.push_binary(conj, lhs, rhs, operator);
             }
         };
@@ -329,6 +343,9 @@ impl FormatWriter for JoinClause {
     ) -> std::fmt::Result {
         self.ty.format_writer(context)?;
         context.writer.write_char(' ')?;
+        if self.lateral {
+            context.writer.write_str("lateral ")?;
+        }
         if let Some(ref table) = self.maybe_table {
             table.format_writer(context)?;
         }
@@ -342,6 +359,9 @@ impl FormatWriter for JoinClause {
                 context.writer.write_str(" on ")?;
             }
             self.conditions.format_writer(context)?;
+        } else if self.lateral && !matches!(self.ty, JoinType::Cross | JoinType::Natural) {
+            // lateral subqueries usually filter inside, the join still needs an on
+            context.writer.write_str(" on true")?;
         }
         Ok(())
     }
@@ -1,23 +1,40 @@
 pub mod bind;
 mod builder;
 pub mod col;
+pub mod cte;
 pub mod dialect;
+mod error;
 pub mod expr;
 pub mod ident;
 mod insert;
 pub mod join;
+pub mod lock;
+mod merge;
 pub mod raw;
+pub mod union;
 pub mod writer;
 pub mod row;
 
-pub use builder::Builder;
+pub use builder::{Builder, MutationStrategy};
+pub use error::Error;
 pub use insert::InsertBuilder;
+pub use merge::MergeBuilder;
+pub use qraft_derive::{IntoRow, Table};
 pub use row::Row;
 
+// lets the derives refer to `::qraft` from within this crate
+extern crate self as qraft;
+
 use bind::{Bind, IntoBind};
 use col::AliasSub;
 use expr::sub::AliasSubFn;
-use expr::Expr;
+use expr::case::{Case, CaseOn};
+use expr::cast::SqlType;
+use expr::fncall::{
+    Aggregate, AggregateCall, Function, FunctionCall, IntoAggregateArg, IntoFunctionArgs,
+};
+use expr::window::{WindowCall, WindowFunction};
+use expr::{Expr, IntoLhsExpr, IntoRhsExpr};
 use ident::{Ident, IntoIdent};
 use raw::Raw;
 use smol_str::SmolStr;
@@ -80,6 +97,232 @@ where
     Expr::Subquery(Box::new(builder))
 }
 
+pub fn count<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::Count, arg)
+}
+
+pub fn sum<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::Sum, arg)
+}
+
+pub fn avg<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::Avg, arg)
+}
+
+pub fn min<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::Min, arg)
+}
+
+pub fn max<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::Max, arg)
+}
+
+/// `string_agg` on postgres and `group_concat` on mysql and sqlite. Mysql
+/// takes no placeholder for the separator, one with a backslash is written
+/// as a hex literal so it reads the same with `NO_BACKSLASH_ESCAPES`.
+pub fn string_agg<T, S>(arg: T, separator: S) -> AggregateCall
+where
+    T: IntoAggregateArg,
+    S: Into<SmolStr>,
+{
+    AggregateCall::of(Aggregate::StringAgg(separator.into()), arg)
+}
+
+/// `array_agg` on postgres, other dialects collect into a json array.
+pub fn array_agg<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::ArrayAgg, arg)
+}
+
+pub fn json_agg<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::JsonAgg, arg)
+}
+
+pub fn bool_and<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::BoolAnd, arg)
+}
+
+pub fn bool_or<T: IntoAggregateArg>(arg: T) -> AggregateCall {
+    AggregateCall::of(Aggregate::BoolOr, arg)
+}
+
+/// Calls any sql function by name, the arguments are rendered as is so
+/// columns need [`column`] to not be bound as values.
+pub fn func<N, A>(name: N, args: A) -> FunctionCall
+where
+    N: Into<SmolStr>,
+    A: IntoFunctionArgs,
+{
+    FunctionCall::new(Function::Named(name.into()), args.into_function_args())
+}
+
+pub fn lower<C: IntoLhsExpr>(column: C) -> FunctionCall {
+    FunctionCall::new(Function::Lower, vec![column.into_lhs_expr()])
+}
+
+pub fn upper<C: IntoLhsExpr>(column: C) -> FunctionCall {
+    FunctionCall::new(Function::Upper, vec![column.into_lhs_expr()])
+}
+
+pub fn length<C: IntoLhsExpr>(column: C) -> FunctionCall {
+    FunctionCall::new(Function::Length, vec![column.into_lhs_expr()])
+}
+
+pub fn abs<C: IntoLhsExpr>(column: C) -> FunctionCall {
+    FunctionCall::new(Function::Abs, vec![column.into_lhs_expr()])
+}
+
+pub fn round<C: IntoLhsExpr>(column: C) -> FunctionCall {
+    FunctionCall::new(Function::Round, vec![column.into_lhs_expr()])
+}
+
+/// `substring(column, start, length)`, `substr` on sqlite. The start is 1
+/// based.
+pub fn substring<C, S, L>(column: C, start: S, length: L) -> FunctionCall
+where
+    C: IntoLhsExpr,
+    S: IntoRhsExpr,
+    L: IntoRhsExpr,
+{
+    let args = vec![column.into_lhs_expr(), start.into_rhs_expr(), length.into_rhs_expr()];
+    FunctionCall::new(Function::Substring, args)
+}
+
+/// The current timestamp, `datetime('now')` on sqlite.
+pub fn now() -> FunctionCall {
+    FunctionCall::new(Function::Now, Vec::new())
+}
+
+pub fn round_to<C: IntoLhsExpr>(column: C, decimals: u32) -> FunctionCall {
+    let decimals = Raw::new(smol_str::format_smolstr!("{}", decimals));
+    let args = vec![column.into_lhs_expr(), decimals.into_rhs_expr()];
+    FunctionCall::new(Function::Round, args)
+}
+
+pub fn coalesce<C, D>(column: C, default: D) -> FunctionCall
+where
+    C: IntoLhsExpr,
+    D: IntoRhsExpr,
+{
+    let args = vec![column.into_lhs_expr(), default.into_rhs_expr()];
+    FunctionCall::new(Function::Coalesce, args)
+}
+
+pub fn nullif<C, V>(column: C, value: V) -> FunctionCall
+where
+    C: IntoLhsExpr,
+    V: IntoRhsExpr,
+{
+    let args = vec![column.into_lhs_expr(), value.into_rhs_expr()];
+    FunctionCall::new(Function::NullIf, args)
+}
+
+/// Concatenates the arguments as strings, see [`Expr::concat`]. Without any
+/// argument it is the empty string `''`.
+pub fn concat<A: IntoFunctionArgs>(args: A) -> Expr {
+    let mut args = args.into_function_args().into_iter();
+    match args.next() {
+        Some(first) => args.fold(first, |lhs, rhs| lhs.concat(rhs)),
+        None => lit("").into_rhs_expr(),
+    }
+}
+
+pub fn cast<E: IntoLhsExpr>(expr: E, ty: SqlType) -> Expr {
+    expr.into_lhs_expr().cast(ty)
+}
+
+/// The value an upsert tried to insert, `excluded."col"` on postgres and
+/// sqlite, `values(col)` on mysql.
+pub fn excluded<C: IntoIdent>(column: C) -> Expr {
+    Expr::Excluded(column.into_ident())
+}
+
+/// The column default in an insert, `Builder::update` leaves the column
+/// untouched.
+pub fn default_value() -> Expr {
+    Expr::Default
+}
+
+pub fn case() -> Case {
+    Case::new()
+}
+
+pub fn case_on<O: IntoLhsExpr>(operand: O) -> CaseOn {
+    CaseOn::new(operand)
+}
+
+pub fn row_number() -> WindowCall {
+    WindowCall::new(WindowFunction::RowNumber, Vec::new())
+}
+
+pub fn rank() -> WindowCall {
+    WindowCall::new(WindowFunction::Rank, Vec::new())
+}
+
+pub fn dense_rank() -> WindowCall {
+    WindowCall::new(WindowFunction::DenseRank, Vec::new())
+}
+
+pub fn percent_rank() -> WindowCall {
+    WindowCall::new(WindowFunction::PercentRank, Vec::new())
+}
+
+pub fn cume_dist() -> WindowCall {
+    WindowCall::new(WindowFunction::CumeDist, Vec::new())
+}
+
+pub fn ntile(buckets: usize) -> WindowCall {
+    let buckets = Raw::new(smol_str::format_smolstr!("{}", buckets));
+    WindowCall::new(WindowFunction::Ntile, vec![buckets.into_rhs_expr()])
+}
+
+pub fn lag<C: IntoLhsExpr>(column: C, offset: usize) -> WindowCall {
+    let offset = Raw::new(smol_str::format_smolstr!("{}", offset));
+    let args = vec![column.into_lhs_expr(), offset.into_rhs_expr()];
+    WindowCall::new(WindowFunction::Lag, args)
+}
+
+pub fn lag_or<C, D>(column: C, offset: usize, default: D) -> WindowCall
+where
+    C: IntoLhsExpr,
+    D: IntoRhsExpr,
+{
+    let offset = Raw::new(smol_str::format_smolstr!("{}", offset));
+    let args = vec![
+        column.into_lhs_expr(),
+        offset.into_rhs_expr(),
+        default.into_rhs_expr(),
+    ];
+    WindowCall::new(WindowFunction::Lag, args)
+}
+
+pub fn lead<C: IntoLhsExpr>(column: C, offset: usize) -> WindowCall {
+    let offset = Raw::new(smol_str::format_smolstr!("{}", offset));
+    let args = vec![column.into_lhs_expr(), offset.into_rhs_expr()];
+    WindowCall::new(WindowFunction::Lead, args)
+}
+
+pub fn lead_or<C, D>(column: C, offset: usize, default: D) -> WindowCall
+where
+    C: IntoLhsExpr,
+    D: IntoRhsExpr,
+{
+    let offset = Raw::new(smol_str::format_smolstr!("{}", offset));
+    let args = vec![
+        column.into_lhs_expr(),
+        offset.into_rhs_expr(),
+        default.into_rhs_expr(),
+    ];
+    WindowCall::new(WindowFunction::Lead, args)
+}
+
+pub fn first_value<C: IntoLhsExpr>(column: C) -> WindowCall {
+    WindowCall::new(WindowFunction::FirstValue, vec![column.into_lhs_expr()])
+}
+
+pub fn last_value<C: IntoLhsExpr>(column: C) -> WindowCall {
+    WindowCall::new(WindowFunction::LastValue, vec![column.into_lhs_expr()])
+}
+
 #[macro_export]
 macro_rules! row {
     ( $( $key:ident => $val:expr ),* $(,)? ) => {{
@@ -0,0 +1,75 @@
+use crate::{
+    dialect::Dialect,
+    insert::Columns,
+    writer::{FormatContext, FormatWriter},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockStrength {
+    Update,
+    Share,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LockWait {
+    #[default]
+    Wait,
+    NoWait,
+    SkipLocked,
+}
+
+/// The options are kept until a strength is picked, nothing is rendered
+/// without one.
+#[derive(Debug, Default, Clone)]
+pub struct Lock {
+    pub(crate) maybe_strength: Option<LockStrength>,
+    pub(crate) wait: LockWait,
+    pub(crate) maybe_of: Option<Columns>,
+    // rejects the lock on sqlite instead of dropping it
+    pub(crate) strict: bool,
+}
+
+impl Lock {
+    pub(crate) fn is_locking(&self) -> bool {
+        self.maybe_strength.is_some()
+    }
+}
+
+impl FormatWriter for Lock {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        // sqlite locks the whole database file, there is no row locking
+        let Some(strength) = self.maybe_strength else {
+            return Ok(());
+        };
+        if matches!(context.dialect, Dialect::Sqlite) {
+            return Ok(());
+        }
+
+        let is_plain = self.wait == LockWait::Wait && self.maybe_of.is_none();
+        match strength {
+            LockStrength::Update => context.writer.write_str(" for update")?,
+            // older mysql versions only understand the legacy syntax, which
+            // has no options
+            LockStrength::Share if is_plain && matches!(context.dialect, Dialect::MySql) => {
+                return context.writer.write_str(" lock in share mode");
+            }
+            LockStrength::Share => context.writer.write_str(" for share")?,
+        }
+
+        if let Some(ref of) = self.maybe_of
+            && !of.is_empty()
+        {
+            context.writer.write_str(" of ")?;
+            of.format_writer(context)?;
+        }
+
+        match self.wait {
+            LockWait::Wait => Ok(()),
+            LockWait::NoWait => context.writer.write_str(" nowait"),
+            LockWait::SkipLocked => context.writer.write_str(" skip locked"),
+        }
+    }
+}
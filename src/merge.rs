@@ -0,0 +1,639 @@
+#[cfg(any(
+    feature = "postgres",
+    feature = "sqlite",
+    feature = "mysql",
+    feature = "any"
+))]
+use crate::dialect::HasExecutorDialect;
+use crate::{
+    Builder, Error, Ident,
+    bind::Binds,
+    col::IntoTable,
+    dialect::{Dialect, HasDialect},
+    expr::{Expr, cond::Conditions},
+    ident::{IntoIdent, TableRef, split_alias},
+    row::{IntoRow, Row},
+    writer::{FormatContext, FormatWriter},
+};
+
+#[derive(Debug, Clone)]
+enum MergeAction {
+    Update(Row),
+    Delete,
+    Insert(Row),
+}
+
+#[derive(Debug, Clone)]
+struct MergeClause {
+    matched: bool,
+    maybe_condition: Option<Conditions>,
+    action: MergeAction,
+}
+
+/// A `merge into` statement, postgres 15+. Sqlite and mysql render it as an
+/// upsert when the merge is one, see [`MergeBuilder::try_to_sql`].
+#[derive(Debug, Clone)]
+pub struct MergeBuilder {
+    target: Ident,
+    maybe_source: Option<TableRef>,
+    maybe_on: Option<Conditions>,
+    clauses: Vec<MergeClause>,
+}
+
+// what an upsert needs from a merge
+struct UpsertPlan<'a> {
+    keys: Vec<&'a str>,
+    insert: &'a Row,
+    maybe_update: Option<&'a Row>,
+}
+
+impl MergeBuilder {
+    pub fn merge_into<T: IntoIdent>(target: T) -> Self {
+        Self {
+            target: target.into_ident(),
+            maybe_source: None,
+            maybe_on: None,
+            clauses: Vec::new(),
+        }
+    }
+
+    /// A table or a subquery from [`sub_as`].
+    ///
+    /// [`sub_as`]: crate::sub_as
+    pub fn using<S: IntoTable>(&mut self, source: S) -> &mut Self {
+        self.maybe_source = Some(source.into_table());
+        self
+    }
+
+    pub fn on<F>(&mut self, predicate: F) -> &mut Self
+    where
+        F: FnOnce(&mut Builder),
+    {
+        let conditions = Builder::where_conditions(predicate);
+        let target = self.maybe_on.get_or_insert_default();
+        for condition in conditions.0 {
+            target.push(condition);
+        }
+        self
+    }
+
+    pub fn when_matched_update<R: IntoRow>(&mut self, row: R) -> &mut Self {
+        self.push_clause(true, None, MergeAction::Update(row.into_row()))
+    }
+
+    pub fn when_matched_update_where<F, R>(&mut self, predicate: F, row: R) -> &mut Self
+    where
+        F: FnOnce(&mut Builder),
+        R: IntoRow,
+    {
+        let condition = Builder::where_conditions(predicate);
+        self.push_clause(true, Some(condition), MergeAction::Update(row.into_row()))
+    }
+
+    pub fn when_matched_delete(&mut self) -> &mut Self {
+        self.push_clause(true, None, MergeAction::Delete)
+    }
+
+    pub fn when_matched_delete_where<F>(&mut self, predicate: F) -> &mut Self
+    where
+        F: FnOnce(&mut Builder),
+    {
+        let condition = Builder::where_conditions(predicate);
+        self.push_clause(true, Some(condition), MergeAction::Delete)
+    }
+
+    pub fn when_not_matched_insert<R: IntoRow>(&mut self, row: R) -> &mut Self {
+        self.push_clause(false, None, MergeAction::Insert(row.into_row()))
+    }
+
+    pub fn when_not_matched_insert_where<F, R>(&mut self, predicate: F, row: R) -> &mut Self
+    where
+        F: FnOnce(&mut Builder),
+        R: IntoRow,
+    {
+        let condition = Builder::where_conditions(predicate);
+        self.push_clause(false, Some(condition), MergeAction::Insert(row.into_row()))
+    }
+
+    fn push_clause(
+        &mut self,
+        matched: bool,
+        maybe_condition: Option<Conditions>,
+        action: MergeAction,
+    ) -> &mut Self {
+        self.clauses.push(MergeClause {
+            matched,
+            maybe_condition,
+            action,
+        });
+        self
+    }
+
+    /// Renders the sql alone, binds are left as placeholders.
+    ///
+    /// # Panics
+    ///
+    /// Past the bind limit of the dialect, [`MergeBuilder::try_to_sql`] returns
+    /// [`Error::TooManyBinds`] instead.
+    pub fn to_sql<Database: HasDialect>(&self) -> String {
+        self.to_sql_for(Database::DIALECT)
+    }
+
+    pub fn to_sql_for(&self, dialect: Dialect) -> String {
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::new(&mut str, dialect);
+        if let Err(err) = self.format_writer(&mut context) {
+            panic!("{}", context.render_error(err));
+        }
+        str
+    }
+
+    /// Renders the sql along with its bindings, gathered in placeholder
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Past the bind limit of the dialect or on a `u64` bind above `i64::MAX`
+    /// on postgres and sqlite, [`MergeBuilder::try_build_sql`] returns
+    /// [`Error::TooManyBinds`] or [`Error::BindOutOfRange`] instead.
+    pub fn build_sql<Database: HasDialect>(&self) -> (String, Binds) {
+        self.build_sql_for(Database::DIALECT)
+    }
+
+    pub fn build_sql_for(&self, dialect: Dialect) -> (String, Binds) {
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::with_binds(&mut str, dialect);
+        if let Err(err) = self.format_writer(&mut context) {
+            panic!("{}", context.render_error(err));
+        }
+        let binds = context.take_binds();
+        (str, binds)
+    }
+
+    /// Like [`MergeBuilder::to_sql`] but rejects a merge sqlite or mysql can
+    /// not run. They get an `insert ... select ... on conflict` when the `on`
+    /// only compares target columns for equality, backed by a unique index,
+    /// with one unconditional insert setting those columns from the source
+    /// and at most one unconditional update reusing the inserted values or
+    /// binds. Mysql's `on duplicate key update` matches any unique key of the
+    /// target, not only the `on` columns.
+    pub fn try_to_sql<Database: HasDialect>(&self) -> Result<String, Error> {
+        self.try_to_sql_for(Database::DIALECT)
+    }
+
+    pub fn try_to_sql_for(&self, dialect: Dialect) -> Result<String, Error> {
+        self.validate(dialect)?;
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::new(&mut str, dialect);
+        self.format_writer(&mut context)
+            .map_err(|err| context.render_error(err))?;
+        Ok(str)
+    }
+
+    pub fn try_build_sql<Database: HasDialect>(&self) -> Result<(String, Binds), Error> {
+        self.try_build_sql_for(Database::DIALECT)
+    }
+
+    pub fn try_build_sql_for(&self, dialect: Dialect) -> Result<(String, Binds), Error> {
+        self.try_build_sql_with(dialect, false)
+    }
+
+    // the any driver has no arrays, they go one placeholder per element
+    pub(crate) fn try_build_sql_with(
+        &self,
+        dialect: Dialect,
+        expand_arrays: bool,
+    ) -> Result<(String, Binds), Error> {
+        self.validate(dialect)?;
+        let size_hint = 64;
+        let mut str = String::with_capacity(size_hint);
+        let mut context = FormatContext::with_binds(&mut str, dialect);
+        context.expand_arrays = expand_arrays;
+        self.format_writer(&mut context)
+            .map_err(|err| context.render_error(err))?;
+        let binds = context.take_binds();
+        Ok((str, binds))
+    }
+
+    fn validate(&self, dialect: Dialect) -> Result<(), Error> {
+        let Some(ref source) = self.maybe_source else {
+            return Err(Error::IncompleteMerge);
+        };
+        if self.maybe_on.is_none() || self.clauses.is_empty() {
+            return Err(Error::IncompleteMerge);
+        }
+
+        if let TableRef::AliasSub(sub) = source {
+            sub.inner.validate(dialect)?;
+        }
+        let conditions = self
+            .clauses
+            .iter()
+            .filter_map(|clause| clause.maybe_condition.as_ref());
+        for condition in self.maybe_on.iter().chain(conditions) {
+            condition.validate(dialect)?;
+        }
+
+        if !matches!(dialect, Dialect::Postgres) {
+            self.upsert_plan()
+                .map_err(|reason| Error::Unsupported { dialect, reason })?;
+        }
+        Ok(())
+    }
+
+    fn upsert_plan<'a>(&'a self) -> Result<UpsertPlan<'a>, &'static str> {
+        let (name, alias) = split_alias(self.target.as_str());
+        let qualifier = alias.unwrap_or(name);
+
+        let pairs = self
+            .maybe_on
+            .as_ref()
+            .and_then(|on| on.column_equalities())
+            .ok_or("merge on must only compare columns for equality")?;
+        let target_column = |ident: &'a Ident| match ident.as_str().rsplit_once('.') {
+            Some((table, column)) if table == qualifier => Some(column),
+            _ => None,
+        };
+        let mut pending = Vec::new();
+        for (lhs, rhs) in pairs {
+            let (key, source) = match (target_column(lhs), target_column(rhs)) {
+                (Some(key), None) => (key, rhs),
+                (None, Some(key)) => (key, lhs),
+                _ => return Err("merge on must compare a target column to a source column"),
+            };
+            pending.push((key, source));
+        }
+
+        let mut maybe_insert = None;
+        let mut maybe_update = None;
+        for clause in &self.clauses {
+            if clause.maybe_condition.is_some() {
+                return Err("conditional when clauses need a merge statement");
+            }
+            match clause.action {
+                MergeAction::Insert(ref row) if maybe_insert.is_none() => maybe_insert = Some(row),
+                MergeAction::Update(ref row) if maybe_update.is_none() => maybe_update = Some(row),
+                MergeAction::Delete => return Err("an upsert can not delete"),
+                _ => return Err("an upsert has one insert and one update"),
+            }
+        }
+        let insert = maybe_insert.ok_or("an upsert needs a when not matched insert")?;
+
+        // the conflict only stands for the on clause when the inserted keys
+        // are the source values it compares
+        let mut keys = Vec::new();
+        for (key, source) in pending {
+            let inserted = insert
+                .values
+                .iter()
+                .find_map(|(column, expr)| (column.as_str() == key).then_some(expr));
+            match inserted {
+                Some(Expr::Ident(TableRef::Ident(ident))) if ident == source => keys.push(key),
+                _ => return Err("merge inserts must set the on columns from the source"),
+            }
+        }
+
+        if let Some(update) = maybe_update {
+            for expr in update.values.values() {
+                match expr {
+                    Expr::Bind(_) => {}
+                    Expr::Ident(TableRef::Ident(ident))
+                        if excluded_column(insert, ident).is_some() => {}
+                    _ => return Err("merge updates must reuse the inserted values"),
+                }
+            }
+        }
+
+        Ok(UpsertPlan {
+            keys,
+            insert,
+            maybe_update,
+        })
+    }
+
+    #[cfg(any(
+        feature = "postgres",
+        feature = "sqlite",
+        feature = "mysql",
+        feature = "any"
+    ))]
+    pub async fn execute<DB, E>(
+        &self,
+        executor: E,
+    ) -> Result<<DB as sqlx::Database>::QueryResult, Error>
+    where
+        DB: sqlx::Database,
+        E: for<'c> sqlx::Executor<'c, Database = DB> + HasExecutorDialect,
+        Binds: for<'c> sqlx::IntoArguments<'c, DB>,
+        <DB as sqlx::Database>::QueryResult: crate::dialect::HasRowsAffected,
+    {
+        let (sql, bindings) =
+            self.try_build_sql_with(executor.dialect()?, executor.expands_arrays())?;
+        let result = sqlx::query_with::<_, _>(&sql, bindings)
+            .execute(executor)
+            .await?;
+        Ok(result)
+    }
+
+    fn format_merge<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        context.writer.write_str("merge into ")?;
+        self.target.format_writer(context)?;
+        if let Some(ref source) = self.maybe_source {
+            context.writer.write_str(" using ")?;
+            source.format_writer(context)?;
+        }
+        if let Some(ref on) = self.maybe_on {
+            context.writer.write_str(" on ")?;
+            on.format_writer(context)?;
+        }
+
+        for clause in &self.clauses {
+            if clause.matched {
+                context.writer.write_str(" when matched")?;
+            } else {
+                context.writer.write_str(" when not matched")?;
+            }
+            if let Some(ref condition) = clause.maybe_condition
+                && !condition.is_empty()
+            {
+                context.writer.write_str(" and ")?;
+                condition.format_writer(context)?;
+            }
+            context.writer.write_str(" then ")?;
+            match clause.action {
+                MergeAction::Update(ref row) => {
+                    context.writer.write_str("update set ")?;
+                    for (index, (column, expr)) in row.values.iter().enumerate() {
+                        if index > 0 {
+                            context.writer.write_str(", ")?;
+                        }
+                        column.format_writer(context)?;
+                        context.writer.write_str(" = ")?;
+                        expr.format_writer(context)?;
+                    }
+                }
+                MergeAction::Delete => context.writer.write_str("delete")?,
+                MergeAction::Insert(ref row) => {
+                    context.writer.write_str("insert (")?;
+                    write_columns(row, context)?;
+                    context.writer.write_str(") values (")?;
+                    write_values(row, context)?;
+                    context.writer.write_char(')')?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn format_upsert<W: std::fmt::Write>(
+        &self,
+        plan: &UpsertPlan<'_>,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        let is_mysql = matches!(context.dialect, Dialect::MySql);
+        if is_mysql && plan.maybe_update.is_none() {
+            context.writer.write_str("insert ignore into ")?;
+        } else {
+            context.writer.write_str("insert into ")?;
+        }
+        // neither accepts the target alias here
+        let (name, _) = split_alias(self.target.as_str());
+        context.write_table(name)?;
+        context.writer.write_str(" (")?;
+        write_columns(plan.insert, context)?;
+        context.writer.write_str(") select ")?;
+        write_values(plan.insert, context)?;
+        if let Some(ref source) = self.maybe_source {
+            context.writer.write_str(" from ")?;
+            source.format_writer(context)?;
+        }
+
+        if is_mysql {
+            if let Some(update) = plan.maybe_update {
+                context.writer.write_str(" on duplicate key update ")?;
+                write_upsert_sets(plan.insert, update, context)?;
+            }
+            return Ok(());
+        }
+
+        // the where keeps sqlite from reading the on conflict as a join
+        context.writer.write_str(" where true on conflict (")?;
+        for (index, key) in plan.keys.iter().enumerate() {
+            if index > 0 {
+                context.writer.write_str(", ")?;
+            }
+            context.write_ident(key)?;
+        }
+        context.writer.write_char(')')?;
+        match plan.maybe_update {
+            Some(update) => {
+                context.writer.write_str(" do update set ")?;
+                write_upsert_sets(plan.insert, update, context)
+            }
+            None => context.writer.write_str(" do nothing"),
+        }
+    }
+}
+
+// the inserted column holding the same source column
+fn excluded_column<'a>(insert: &'a Row, source: &Ident) -> Option<&'a Ident> {
+    insert.values.iter().find_map(|(column, expr)| match expr {
+        Expr::Ident(TableRef::Ident(ident)) if ident == source => Some(column),
+        _ => None,
+    })
+}
+
+fn write_columns<W: std::fmt::Write>(
+    row: &Row,
+    context: &mut FormatContext<'_, W>,
+) -> std::fmt::Result {
+    for (index, column) in row.values.keys().enumerate() {
+        if index > 0 {
+            context.writer.write_str(", ")?;
+        }
+        column.format_writer(context)?;
+    }
+    Ok(())
+}
+
+fn write_values<W: std::fmt::Write>(
+    row: &Row,
+    context: &mut FormatContext<'_, W>,
+) -> std::fmt::Result {
+    for (index, expr) in row.values.values().enumerate() {
+        if index > 0 {
+            context.writer.write_str(", ")?;
+        }
+        expr.format_writer(context)?;
+    }
+    Ok(())
+}
+
+fn write_upsert_sets<W: std::fmt::Write>(
+    insert: &Row,
+    update: &Row,
+    context: &mut FormatContext<'_, W>,
+) -> std::fmt::Result {
+    for (index, (column, expr)) in update.values.iter().enumerate() {
+        if index > 0 {
+            context.writer.write_str(", ")?;
+        }
+        column.format_writer(context)?;
+        context.writer.write_str(" = ")?;
+        match expr {
+            Expr::Ident(TableRef::Ident(ident)) => match excluded_column(insert, ident) {
+                Some(inserted) => Expr::Excluded(inserted.clone()).format_writer(context)?,
+                None => expr.format_writer(context)?,
+            },
+            _ => expr.format_writer(context)?,
+        }
+    }
+    Ok(())
+}
+
+impl FormatWriter for MergeBuilder {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        if !matches!(context.dialect, Dialect::Postgres)
+            && let Ok(plan) = self.upsert_plan()
+        {
+            return self.format_upsert(&plan, context);
+        }
+        self.format_merge(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        column,
+        dialect::{MySql, Postgres, Sqlite},
+        sub_as,
+    };
+
+    use super::*;
+
+    fn sync_users() -> MergeBuilder {
+        let mut merge = MergeBuilder::merge_into("users as u");
+        merge
+            .using(sub_as(
+                |b: &mut Builder| {
+                    b.select(["id", "name"])
+                        .from("staging")
+                        .where_eq("batch", 7);
+                },
+                "s",
+            ))
+            .on(|on| {
+                on.where_column("u.id", '=', "s.id");
+            })
+            .when_matched_update(|row: &mut Row| {
+                row.field("name", column("s.name")).field("synced", true);
+            })
+            .when_not_matched_insert(|row: &mut Row| {
+                row.field("id", column("s.id"))
+                    .field("name", column("s.name"));
+            });
+        merge
+    }
+
+    #[test]
+    fn test_merge() {
+        let merge = sync_users();
+        assert_eq!(
+            r#"merge into "users" as "u" using (select "id", "name" from "staging" where "batch" = $1) as "s" on "u"."id" = "s"."id" when matched then update set "name" = "s"."name", "synced" = $2 when not matched then insert ("id", "name") values ("s"."id", "s"."name")"#,
+            merge.try_to_sql::<Postgres>().unwrap()
+        );
+        assert_eq!(
+            r#"insert into "users" ("id", "name") select "s"."id", "s"."name" from (select "id", "name" from "staging" where "batch" = ?1) as "s" where true on conflict ("id") do update set "name" = "excluded"."name", "synced" = ?2"#,
+            merge.try_to_sql::<Sqlite>().unwrap()
+        );
+        assert_eq!(
+            "insert into `users` (`id`, `name`) select `s`.`id`, `s`.`name` from (select `id`, `name` from `staging` where `batch` = ?) as `s` on duplicate key update `name` = values(`name`), `synced` = ?",
+            merge.try_to_sql::<MySql>().unwrap()
+        );
+        assert_eq!(merge.build_sql::<MySql>().1.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_unsupported() {
+        let mut merge = sync_users();
+        merge.when_matched_delete_where(|b| {
+            b.where_eq("u.active", false);
+        });
+        assert!(
+            merge
+                .try_to_sql::<Postgres>()
+                .unwrap()
+                .ends_with(r#"when matched and "u"."active" = $3 then delete"#)
+        );
+        assert!(matches!(
+            merge.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let mut merge = MergeBuilder::merge_into("users");
+        merge
+            .using("staging")
+            .on(|on| {
+                on.where_column("users.id", '=', "staging.id");
+            })
+            .when_not_matched_insert(|row: &mut Row| {
+                row.field("id", column("staging.id"));
+            });
+        assert_eq!(
+            "insert ignore into `users` (`id`) select `staging`.`id` from `staging`",
+            merge.try_to_sql::<MySql>().unwrap()
+        );
+        merge.when_matched_update(|row: &mut Row| {
+            row.field("name", column("staging.name"));
+        });
+        assert!(matches!(
+            merge.try_to_sql::<MySql>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        // the inserted key must be the source column the on compares
+        let mut merge = MergeBuilder::merge_into("users as u");
+        merge
+            .using("staging as s")
+            .on(|on| {
+                on.where_column("u.email", '=', "s.email");
+            })
+            .when_not_matched_insert(|row: &mut Row| {
+                row.field("id", column("s.id"));
+            });
+        assert!(matches!(
+            merge.try_to_sql::<Sqlite>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let mut merge = MergeBuilder::merge_into("users as u");
+        merge
+            .using("staging as s")
+            .on(|on| {
+                on.where_column("u.id", '=', "s.id");
+            })
+            .when_not_matched_insert(|row: &mut Row| {
+                row.field("id", column("s.other_id"));
+            });
+        assert!(matches!(
+            merge.try_to_sql::<MySql>(),
+            Err(Error::Unsupported { .. })
+        ));
+
+        let merge = MergeBuilder::merge_into("users");
+        assert!(matches!(
+            merge.try_to_sql::<Postgres>(),
+            Err(Error::IncompleteMerge)
+        ));
+    }
+}
@@ -1,34 +1,45 @@
 use smol_str::SmolStr;
 
 use crate::{
+    bind::{Binds, IntoBinds},
     dialect::Dialect,
     writer::{self, FormatWriter},
 };
 
+// the bindings of the raw placeholders travel with the sql
 #[derive(Debug, Clone)]
-pub struct Raw(pub(crate) SmolStr);
+pub struct Raw(pub(crate) SmolStr, pub(crate) Binds);
 
 impl Raw {
     pub fn new<T>(value: T) -> Self
     where
         T: Into<SmolStr>,
     {
-        Self(value.into())
+        Self(value.into(), Binds::None)
     }
 
     pub fn new_static(value: &'static str) -> Self {
-        Self(SmolStr::new_static(value))
+        Self(SmolStr::new_static(value), Binds::None)
+    }
+
+    pub fn bind<B: IntoBinds>(mut self, binds: B) -> Self {
+        self.1.append(binds.into_binds());
+        self
     }
 }
 
 impl FormatWriter for Raw {
+    #[allow(clippy::collapsible_if)]
     fn format_writer<W: std::fmt::Write>(
         &self,
         context: &mut writer::FormatContext<'_, W>,
     ) -> std::fmt::Result {
         let sql = self.0.as_str();
+        context.push_binds(&self.1)?;
 
         if !matches!(context.dialect, Dialect::Postgres) {
+            // keep numbering the following sqlite placeholders after ours
+            context.reserve_placeholders(self.1.len())?;
             return context.writer.write_str(sql);
         }
 
@@ -1,12 +1,15 @@
 use indexmap::IndexMap;
 
 use crate::{
-    bind::Binds, expr::{Expr, IntoRhsExpr, TakeBindings}, ident::IntoIdent, writer::{FormatContext, FormatWriter}, Ident
+    bind::Binds, expr::{Expr, IntoRhsExpr}, ident::IntoIdent, writer::{FormatContext, FormatWriter}, Ident
 };
 
 #[derive(Debug, Clone, Default)]
 pub struct Row {
     pub values: IndexMap<Ident, Expr>,
+    /// Always empty, the binds of the values are gathered when the query is
+    /// rendered.
+    #[deprecated(note = "binds are gathered while rendering, use `build_sql` to get them")]
     pub binds: Binds,
 }
 
@@ -33,6 +36,7 @@ where
 
 impl Row {
     pub fn new() -> Self {
+        #[allow(deprecated)]
         Self {
             values: IndexMap::new(),
             binds: Binds::None,
@@ -45,8 +49,7 @@ impl Row {
         V: IntoRhsExpr,
     {
         let col_ident = columns.into_ident();
-        let mut expr = value.into_rhs_expr();
-        self.binds.append(expr.take_bindings());
+        let expr = value.into_rhs_expr();
         self.values.insert(col_ident, expr);
         self
     }
@@ -68,19 +71,14 @@ impl Row {
 
     pub fn build(&mut self) -> Self {
         // take mem
+        #[allow(deprecated)]
         Row {
             values: std::mem::take(&mut self.values),
-            binds: self.binds.take(),
+            binds: Binds::None,
         }
     }
 }
 
-impl TakeBindings for Row {
-    fn take_bindings(&mut self) -> Binds {
-        self.binds.take()
-    }
-}
-
 impl FormatWriter for Row {
     fn format_writer<W: std::fmt::Write>(&self, context: &mut crate::writer::FormatContext<'_, W>) -> std::fmt::Result {
         // format for all dialect insert values (<rows>)
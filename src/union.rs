@@ -0,0 +1,129 @@
+use crate::{
+    Builder,
+    dialect::Dialect,
+    expr::order::Order,
+    writer::{FormatContext, FormatWriter},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum UnionType {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+impl FormatWriter for UnionType {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        match self {
+            UnionType::Union => context.writer.write_str("union"),
+            UnionType::UnionAll => context.writer.write_str("union all"),
+            UnionType::Intersect => context.writer.write_str("intersect"),
+            UnionType::Except => context.writer.write_str("except"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnionClause {
+    ty: UnionType,
+    query: Box<Builder>,
+}
+
+impl UnionClause {
+    pub(crate) fn new(ty: UnionType, query: Builder) -> Self {
+        Self {
+            ty,
+            query: Box::new(query),
+        }
+    }
+}
+
+impl FormatWriter for UnionClause {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        self.ty.format_writer(context)?;
+        context.writer.write_char(' ')?;
+        if self.query.is_compound_operand() {
+            self.query.format_writer(context)
+        } else {
+            write_operand_open(context)?;
+            self.query.format_writer(context)?;
+            write_operand_close(context)
+        }
+    }
+}
+
+/// Set operations applied to the select, the ordering and pagination set
+/// once a union is added apply to the whole compound result.
+#[derive(Debug, Default, Clone)]
+pub struct Unions {
+    clauses: Vec<UnionClause>,
+    pub(crate) maybe_order: Option<Order>,
+    pub(crate) maybe_limit: Option<usize>,
+    pub(crate) maybe_offset: Option<usize>,
+}
+
+impl Unions {
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, clause: UnionClause) {
+        self.clauses.push(clause);
+    }
+
+    pub(crate) fn queries(&self) -> impl Iterator<Item = &Builder> {
+        self.clauses.iter().map(|clause| clause.query.as_ref())
+    }
+}
+
+impl FormatWriter for Unions {
+    fn format_writer<W: std::fmt::Write>(
+        &self,
+        context: &mut FormatContext<'_, W>,
+    ) -> std::fmt::Result {
+        for clause in &self.clauses {
+            context.writer.write_char(' ')?;
+            clause.format_writer(context)?;
+        }
+
+        if let Some(ref order) = self.maybe_order
+            && !order.is_empty()
+        {
+            context.writer.write_str(" order by ")?;
+            order.format_writer(context)?;
+        }
+
+        if let Some(limit) = self.maybe_limit {
+            write!(context.writer, " limit {}", limit)?;
+        }
+
+        if let Some(offset) = self.maybe_offset {
+            write!(context.writer, " offset {}", offset)?;
+        }
+
+        Ok(())
+    }
+}
+
+// sqlite does not allow parenthesized selects inside a compound select
+pub(crate) fn write_operand_open<W: std::fmt::Write>(
+    context: &mut FormatContext<'_, W>,
+) -> std::fmt::Result {
+    match context.dialect {
+        Dialect::Postgres | Dialect::MySql => context.writer.write_char('('),
+        Dialect::Sqlite => context.writer.write_str("select * from ("),
+    }
+}
+
+pub(crate) fn write_operand_close<W: std::fmt::Write>(
+    context: &mut FormatContext<'_, W>,
+) -> std::fmt::Result {
+    context.writer.write_char(')')
+}
@@ -1,6 +1,10 @@
 use std::{fmt::Write, ops::Deref};
 
-use crate::{Ident, dialect::Dialect};
+use crate::{
+    Error, Ident,
+    bind::{Bind, Binds},
+    dialect::Dialect,
+};
 
 pub(crate) trait FormatWriter {
     fn format_writer<W: Write>(&self, context: &mut FormatContext<'_, W>) -> std::fmt::Result;
@@ -10,6 +14,12 @@ pub(crate) struct FormatContext<'a, W: Write> {
     pub(crate) writer: &'a mut W,
     pub(crate) dialect: Dialect,
     pub(crate) placeholder: u16,
+    // bindings are gathered in render order, only when requested
+    pub(crate) maybe_binds: Option<Binds>,
+    // arrays are written one placeholder per element, even on postgres
+    pub(crate) expand_arrays: bool,
+    overflowed: bool,
+    out_of_range: bool,
 }
 
 impl<'a, W: Write> Deref for FormatContext<'a, W> {
@@ -32,9 +42,59 @@ impl<'a, W: Write> FormatContext<'a, W> {
             writer,
             dialect,
             placeholder: 0,
+            maybe_binds: None,
+            expand_arrays: false,
+            overflowed: false,
+            out_of_range: false,
         }
     }
 
+    pub fn with_binds(writer: &'a mut W, dialect: Dialect) -> Self {
+        Self {
+            writer,
+            dialect,
+            placeholder: 0,
+            maybe_binds: Some(Binds::None),
+            expand_arrays: false,
+            overflowed: false,
+            out_of_range: false,
+        }
+    }
+
+    pub(crate) fn take_binds(&mut self) -> Binds {
+        self.maybe_binds.take().unwrap_or_default()
+    }
+
+    pub(crate) fn write_bind(&mut self, bind: &Bind) -> std::fmt::Result {
+        self.check_range(bind)?;
+        if let Some(ref mut binds) = self.maybe_binds {
+            binds.push(bind.clone());
+        }
+        self.write_placeholder()
+    }
+
+    pub(crate) fn push_binds(&mut self, binds: &Binds) -> std::fmt::Result {
+        for bind in binds {
+            self.check_range(bind)?;
+        }
+        if let Some(ref mut target) = self.maybe_binds {
+            for bind in binds {
+                target.push(bind.clone());
+            }
+        }
+        Ok(())
+    }
+
+    // fails on a value the dialect would store wrapped rather than refuse,
+    // the sql alone does not carry the values
+    fn check_range(&mut self, bind: &Bind) -> std::fmt::Result {
+        if self.maybe_binds.is_none() || bind.fits(self.dialect) {
+            return Ok(());
+        }
+        self.out_of_range = true;
+        Err(std::fmt::Error)
+    }
+
     pub(crate) fn write_table(&mut self, ident: &str) -> std::fmt::Result {
         for (i, part) in ident.split('.').enumerate() {
             if i > 0 {
@@ -86,8 +146,59 @@ impl<'a, W: Write> FormatContext<'a, W> {
         Ok(())
     }
 
+    /// Writes a quoted string literal, for the few places that do not
+    /// accept a placeholder.
+    pub(crate) fn write_str_literal(&mut self, value: &str) -> std::fmt::Result {
+        // mysql reads a backslash as an escape unless NO_BACKSLASH_ESCAPES is
+        // set, a hex literal means the same in both modes
+        if matches!(self.dialect, Dialect::MySql) && value.contains('\\') {
+            self.writer.write_str("x'")?;
+            for byte in value.bytes() {
+                write!(self.writer, "{:02x}", byte)?;
+            }
+            return self.writer.write_char('\'');
+        }
+        self.writer.write_char('\'')?;
+        for char in value.chars() {
+            match char {
+                '\'' => self.writer.write_str("''")?,
+                other => self.writer.write_char(other)?,
+            }
+        }
+        self.writer.write_char('\'')
+    }
+
+    /// Counts placeholders written by someone else, fails past the dialect
+    /// limit rather than wrapping the counter.
+    pub(crate) fn reserve_placeholders(&mut self, count: usize) -> std::fmt::Result {
+        let next = self.placeholder as usize + count;
+        if next > self.dialect.max_binds() {
+            self.overflowed = true;
+            return Err(std::fmt::Error);
+        }
+        self.placeholder = next as u16;
+        Ok(())
+    }
+
+    /// Turns a failed render into an error, telling a placeholder overflow
+    /// or an out of range bind apart from a failing writer.
+    pub(crate) fn render_error(&self, err: std::fmt::Error) -> Error {
+        if self.out_of_range {
+            Error::BindOutOfRange {
+                dialect: self.dialect,
+            }
+        } else if self.overflowed {
+            Error::TooManyBinds {
+                dialect: self.dialect,
+                limit: self.dialect.max_binds(),
+            }
+        } else {
+            Error::Format(err)
+        }
+    }
+
     pub(crate) fn write_placeholder(&mut self) -> std::fmt::Result {
-        self.placeholder += 1;
+        self.reserve_placeholders(1)?;
         match self.dialect {
             Dialect::Postgres => write!(self.writer, "${}", self.placeholder),
             Dialect::MySql => self.writer.write_char('?'),
@@ -0,0 +1,147 @@
+#![cfg(all(feature = "any", feature = "sqlite"))]
+
+use qraft::{Builder, InsertBuilder, Row, dialect::Dialect};
+use sqlx::{AnyPool, any::AnyPoolOptions};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("should build a runtime")
+        .block_on(future)
+}
+
+async fn connect() -> AnyPool {
+    sqlx::any::install_default_drivers();
+    // a single connection keeps the same in memory database
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("should open an in memory database");
+    sqlx::query("create table users (id integer primary key, name text, votes integer)")
+        .execute(&pool)
+        .await
+        .expect("should create the table");
+    pool
+}
+
+#[test]
+fn test_runtime_dialect() {
+    let mut builder = Builder::table("users");
+    builder.where_eq("name", "ovior");
+    for (backend, sql) in [
+        ("PostgreSQL", r#"select * from "users" where "name" = $1"#),
+        ("mysql", "select * from `users` where `name` = ?"),
+        ("sqlite", r#"select * from "users" where "name" = ?1"#),
+    ] {
+        let dialect = Dialect::from_backend_name(backend).expect("should be supported");
+        assert_eq!(sql, builder.to_sql_for(dialect));
+    }
+    assert_eq!(None, Dialect::from_backend_name("mssql"));
+}
+
+#[test]
+fn test_any_pool() {
+    block_on(async {
+        let pool = connect().await;
+        let mut insert = InsertBuilder::insert_into("users");
+        insert.row(
+            Row::new()
+                .field("name", "ovior")
+                .field("votes", 3u8)
+                .build(),
+        );
+        let id = insert
+            .insert_get_id::<sqlx::Any, i64, _>(&pool)
+            .await
+            .expect("should insert the row");
+        assert_eq!(1, id);
+
+        let mut builder = Builder::table("users");
+        builder.select("votes").where_eq("name", "ovior");
+        let votes = builder
+            .value::<sqlx::Any, i64, _>(&pool)
+            .await
+            .expect("should fetch the votes");
+        assert_eq!(3, votes);
+
+        let mut builder = Builder::table("users");
+        builder.where_eq("id", id);
+        let updated = builder
+            .update::<sqlx::Any, _, _>(&pool, Row::new().field("votes", 4).build())
+            .await
+            .expect("should update the row");
+        assert!(updated);
+
+        let mut builder = Builder::table("users");
+        builder.where_eq("votes", 4);
+        let deleted = builder
+            .delete::<sqlx::Any, _>(&pool)
+            .await
+            .expect("should delete the row");
+        assert!(deleted);
+    });
+}
+
+// needs a running server, skipped unless QRAFT_POSTGRES_URL points at one
+#[cfg(feature = "postgres")]
+#[test]
+fn test_any_pool_where_in_on_postgres() {
+    let Ok(url) = std::env::var("QRAFT_POSTGRES_URL") else {
+        return;
+    };
+    block_on(async {
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await
+            .expect("should connect to postgres");
+        sqlx::query("create temporary table users (id bigint primary key, name text)")
+            .execute(&pool)
+            .await
+            .expect("should create the table");
+        sqlx::query("insert into users (id, name) values (1, 'a'), (2, 'b'), (3, 'c')")
+            .execute(&pool)
+            .await
+            .expect("should insert the rows");
+
+        let mut builder = Builder::table("users");
+        builder
+            .select("name")
+            .where_in("id", [1i64, 3])
+            .order_by_asc("id");
+        let names = builder
+            .rows::<sqlx::Any, _>(&pool)
+            .await
+            .expect("should fetch the rows")
+            .iter()
+            .map(|row| sqlx::Row::get::<String, _>(row, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "c"], names);
+
+        // the any driver has no arrays, the list is expanded
+        let mut builder = Builder::table("users");
+        builder
+            .select("name")
+            .where_in_array("id", [1i64, 3])
+            .order_by_asc("id");
+        let names = builder
+            .rows::<sqlx::Any, _>(&pool)
+            .await
+            .expect("should fetch the rows")
+            .iter()
+            .map(|row| sqlx::Row::get::<String, _>(row, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "c"], names);
+
+        let mut builder = Builder::table("users");
+        builder.select("name").where_not_in_array("id", [1i64, 3]);
+        let name = builder
+            .value::<sqlx::Any, String, _>(&pool)
+            .await
+            .expect("should fetch the name");
+        assert_eq!("b", name);
+    });
+}
@@ -0,0 +1,241 @@
+#![cfg(any(feature = "postgres", feature = "sqlite", feature = "mysql"))]
+
+//! Renders every builder feature for each sqlx database and runs them against
+//! sqlite in memory. Mysql and postgres run when `QRAFT_MYSQL_URL` or
+//! `QRAFT_POSTGRES_URL` point to a disposable database.
+
+use qraft::{Builder, Row, case, case_on, dialect::HasDialect, expr::window::Window, row_number};
+
+const SCHEMA: [&str; 4] = [
+    "create temporary table users (id integer primary key, name varchar(64), votes integer)",
+    "create temporary table posts (id integer primary key, user_id integer, title varchar(64))",
+    "insert into users (id, name, votes) values (1, 'ovior', 3), (2, 'dany', 0)",
+    "insert into posts (id, user_id, title) values (1, 1, 'first'), (2, 1, 'second')",
+];
+
+fn queries() -> Vec<(&'static str, Builder)> {
+    let mut queries = Vec::new();
+
+    let mut builder = Builder::table("users");
+    builder
+        .select(["id", "name"])
+        .where_eq("name", "ovior")
+        .or_where_in("id", [1, 2])
+        .where_not_null("votes")
+        .order_by_desc("id")
+        .limit(10)
+        .offset(0);
+    queries.push(("select", builder));
+
+    let mut builder = Builder::table("users");
+    builder
+        .distinct()
+        .select("name")
+        .where_between("votes", 1, 10);
+    queries.push(("distinct between", builder));
+
+    let mut builder = Builder::table("users as u");
+    builder
+        .select("u.name")
+        .add_select(qraft::count("p.id as total"))
+        .join("posts as p", "u.id", "=", "p.user_id")
+        .group_by("u.name")
+        .having("u.name", "!=", "nobody");
+    queries.push(("join group by", builder));
+
+    let mut builder = Builder::table("users");
+    builder.select("name").where_exists(|b: &mut Builder| {
+        b.select_one()
+            .from("posts")
+            .where_column("posts.user_id", "=", "users.id")
+            .where_eq("posts.title", "first");
+    });
+    queries.push(("exists", builder));
+
+    let mut builder = Builder::table("users");
+    builder
+        .select("name")
+        .where_eq("votes", 3)
+        .union_all(|b: &mut Builder| {
+            b.select("title").from("posts");
+        })
+        .order_by_asc("name")
+        .limit(5);
+    queries.push(("union", builder));
+
+    let mut builder = Builder::new();
+    builder
+        .with("active", |b: &mut Builder| {
+            b.select(["id", "name"]).from("users").where_eq("votes", 3);
+        })
+        .from("active")
+        .select("name");
+    queries.push(("with", builder));
+
+    let mut builder = Builder::table("users");
+    builder.select("name").add_select(
+        row_number()
+            .over(|w: &mut Window| {
+                w.partition_by("votes").order_by_desc("id");
+            })
+            .alias("position"),
+    );
+    queries.push(("window", builder));
+
+    let mut builder = Builder::table("users");
+    builder
+        .select(
+            case()
+                .when(
+                    |b: &mut Builder| {
+                        b.where_eq("votes", 0);
+                    },
+                    "none",
+                )
+                .otherwise("some")
+                .alias("bucket"),
+        )
+        .order_by_asc(case_on("name").when("dany", 1).otherwise(2));
+    queries.push(("case", builder));
+
+    let mut builder = Builder::table("posts");
+    builder
+        .select(qraft::count("user_id as authors").distinct())
+        .add_select(qraft::string_agg("title", ", "))
+        .add_select(qraft::count('*').filter(|w| {
+            w.where_eq("title", "first");
+        }));
+    queries.push(("aggregate filter", builder));
+
+    let mut builder = Builder::table("users");
+    builder.where_eq("id", 1).lock_for_update();
+    queries.push(("lock", builder));
+
+    queries
+}
+
+fn assert_dialect<DB: HasDialect, D: HasDialect>() {
+    assert_eq!(DB::DIALECT, D::DIALECT);
+    for (name, builder) in queries() {
+        assert_eq!(
+            builder.to_sql::<D>(),
+            builder.to_sql::<DB>(),
+            "{name} rendered differently"
+        );
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("should build a runtime")
+        .block_on(future)
+}
+
+macro_rules! conformance {
+    ($db:ty, $pool:expr) => {{
+        let pool = $pool;
+        for statement in SCHEMA {
+            sqlx::query(statement)
+                .execute(&pool)
+                .await
+                .expect("should create the schema");
+        }
+
+        for (name, builder) in queries() {
+            let sql = builder.to_sql::<$db>();
+            if let Err(err) = builder.rows::<$db, _>(&pool).await {
+                panic!("{name} failed: {sql}: {err}");
+            }
+        }
+
+        let mut builder = Builder::table("users as u");
+        builder
+            .join("posts as p", "u.id", "=", "p.user_id")
+            .where_not_null("p.title");
+        let updated = builder
+            .update::<$db, _, _>(&pool, Row::new().field("votes", 4).build())
+            .await
+            .expect("should update the joined row");
+        assert!(updated);
+
+        let mut builder = Builder::table("posts");
+        builder.where_eq("user_id", 1);
+        let deleted = builder
+            .delete::<$db, _>(&pool)
+            .await
+            .expect("should delete the rows");
+        assert!(deleted);
+    }};
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_dialect() {
+    assert_dialect::<sqlx::Sqlite, qraft::dialect::Sqlite>();
+}
+
+#[cfg(feature = "mysql")]
+#[test]
+fn test_mysql_dialect() {
+    assert_dialect::<sqlx::MySql, qraft::dialect::MySql>();
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn test_postgres_dialect() {
+    assert_dialect::<sqlx::Postgres, qraft::dialect::Postgres>();
+}
+
+// temporary tables live on a single connection
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_sqlite_conformance() {
+    block_on(async {
+        conformance!(
+            sqlx::Sqlite,
+            sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await
+                .expect("should open an in memory database")
+        );
+    });
+}
+
+#[cfg(feature = "mysql")]
+#[test]
+fn test_mysql_conformance() {
+    let Ok(url) = std::env::var("QRAFT_MYSQL_URL") else {
+        return;
+    };
+    block_on(async {
+        conformance!(
+            sqlx::MySql,
+            sqlx::mysql::MySqlPoolOptions::new()
+                .max_connections(1)
+                .connect(&url)
+                .await
+                .expect("should connect to mysql")
+        );
+    });
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn test_postgres_conformance() {
+    let Ok(url) = std::env::var("QRAFT_POSTGRES_URL") else {
+        return;
+    };
+    block_on(async {
+        conformance!(
+            sqlx::Postgres,
+            sqlx::postgres::PgPoolOptions::new()
+                .max_connections(1)
+                .connect(&url)
+                .await
+                .expect("should connect to postgres")
+        );
+    });
+}
@@ -0,0 +1,508 @@
+#![cfg(feature = "sqlite")]
+
+use qraft::{
+    Builder, InsertBuilder, Row,
+    bind::{Bind, Binds},
+    expr::Expr,
+};
+// the executors are bound on `for<'c> Executor<'c>`, which only a pool satisfies
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("should build a runtime")
+        .block_on(future)
+}
+
+async fn connect() -> SqlitePool {
+    // a single connection keeps the same in memory database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("should open an in memory database");
+    sqlx::query(
+        "create table binds (
+            id integer primary key autoincrement,
+            string text,
+            static_string text,
+            boolean boolean,
+            f32 real,
+            f64 real,
+            i8 integer,
+            i16 integer,
+            i32 integer,
+            i64 integer,
+            u8 integer,
+            u16 integer,
+            u32 integer,
+            u64 integer,
+            bytes blob,
+            date text,
+            time text,
+            timestamptz text,
+            timestamp text,
+            uuid blob,
+            json text
+        )",
+    )
+    .execute(&pool)
+    .await
+    .expect("should create the table");
+    pool
+}
+
+async fn insert(pool: &SqlitePool, row: Row) -> i64 {
+    InsertBuilder::insert_into("binds")
+        .row(row)
+        .insert_get_id::<sqlx::Sqlite, i64, _>(pool)
+        .await
+        .expect("should insert the row")
+}
+
+async fn column<T>(pool: &SqlitePool, id: i64, column: &'static str) -> T
+where
+    T: for<'r> sqlx::Decode<'r, sqlx::Sqlite> + sqlx::Type<sqlx::Sqlite> + Send + Unpin,
+{
+    let mut builder = Builder::table("binds");
+    builder.select(column).where_eq("id", id);
+    builder
+        .value::<sqlx::Sqlite, T, _>(pool)
+        .await
+        .expect("should fetch the column")
+}
+
+#[test]
+fn test_scalar_binds() {
+    block_on(async {
+        let pool = connect().await;
+        let row = Row::new()
+            .field("string", "ovior".to_string())
+            .field("static_string", "static")
+            .field("boolean", true)
+            .field("f32", 1.5f32)
+            .field("f64", 2.25f64)
+            .field("i8", -8i8)
+            .field("i16", -16i16)
+            .field("i32", -32i32)
+            .field("i64", -64i64)
+            .field("u8", 8u8)
+            .field("u16", 16u16)
+            .field("u32", 32u32)
+            .field("u64", 64u64)
+            .field("bytes", Expr::Bind(Bind::VecBytes(Some(vec![1, 2, 3]))))
+            .build();
+        let id = insert(&pool, row).await;
+
+        assert_eq!("ovior", column::<String>(&pool, id, "string").await);
+        assert_eq!("static", column::<String>(&pool, id, "static_string").await);
+        assert!(column::<bool>(&pool, id, "boolean").await);
+        assert_eq!(1.5, column::<f32>(&pool, id, "f32").await);
+        assert_eq!(2.25, column::<f64>(&pool, id, "f64").await);
+        assert_eq!(-8, column::<i8>(&pool, id, "i8").await);
+        assert_eq!(-16, column::<i16>(&pool, id, "i16").await);
+        assert_eq!(-32, column::<i32>(&pool, id, "i32").await);
+        assert_eq!(-64, column::<i64>(&pool, id, "i64").await);
+        assert_eq!(8, column::<u8>(&pool, id, "u8").await);
+        assert_eq!(16, column::<u16>(&pool, id, "u16").await);
+        assert_eq!(32, column::<u32>(&pool, id, "u32").await);
+        assert_eq!(64, column::<i64>(&pool, id, "u64").await);
+        assert_eq!(vec![1u8, 2, 3], column::<Vec<u8>>(&pool, id, "bytes").await);
+    });
+}
+
+#[test]
+fn test_null_binds() {
+    block_on(async {
+        let pool = connect().await;
+        let row = Row::new()
+            .field("string", None::<String>)
+            .field("i64", None::<i64>)
+            .field("u64", None::<u64>)
+            .build();
+        let id = insert(&pool, row).await;
+
+        assert_eq!(None, column::<Option<String>>(&pool, id, "string").await);
+        assert_eq!(None, column::<Option<i64>>(&pool, id, "i64").await);
+        assert_eq!(None, column::<Option<i64>>(&pool, id, "u64").await);
+    });
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time_binds() {
+    use time::{Date, Month, PrimitiveDateTime, Time};
+
+    block_on(async {
+        let pool = connect().await;
+        let date = Date::from_calendar_date(2025, Month::January, 2).unwrap();
+        let time = Time::from_hms(10, 20, 30).unwrap();
+        let timestamp = PrimitiveDateTime::new(date, time);
+        let row = Row::new()
+            .field("date", date)
+            .field("time", time)
+            .field("timestamptz", timestamp.assume_utc())
+            .field("timestamp", timestamp)
+            .build();
+        let id = insert(&pool, row).await;
+
+        assert_eq!(date, column::<Date>(&pool, id, "date").await);
+        assert_eq!(time, column::<Time>(&pool, id, "time").await);
+        assert_eq!(
+            timestamp.assume_utc(),
+            column::<time::OffsetDateTime>(&pool, id, "timestamptz").await
+        );
+        assert_eq!(
+            timestamp,
+            column::<PrimitiveDateTime>(&pool, id, "timestamp").await
+        );
+    });
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono_binds() {
+    use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+
+    block_on(async {
+        let pool = connect().await;
+        let date = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+        let time = NaiveTime::from_hms_opt(10, 20, 30).unwrap();
+        let utc = Utc.with_ymd_and_hms(2025, 1, 2, 10, 20, 30).unwrap();
+        let row = Row::new()
+            .field("date", date)
+            .field("time", time)
+            .field("timestamptz", utc)
+            .field("timestamp", date.and_time(time))
+            .build();
+        let id = insert(&pool, row).await;
+
+        assert_eq!(date, column::<NaiveDate>(&pool, id, "date").await);
+        assert_eq!(time, column::<NaiveTime>(&pool, id, "time").await);
+        assert_eq!(
+            utc,
+            column::<chrono::DateTime<Utc>>(&pool, id, "timestamptz").await
+        );
+        assert_eq!(
+            date.and_time(time),
+            column::<chrono::NaiveDateTime>(&pool, id, "timestamp").await
+        );
+    });
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn test_uuid_binds() {
+    block_on(async {
+        let pool = connect().await;
+        let uuid = uuid::Uuid::from_u128(0x1234_5678_9abc_def0);
+        let id = insert(&pool, Row::new().field("uuid", uuid).build()).await;
+
+        assert_eq!(uuid, column::<uuid::Uuid>(&pool, id, "uuid").await);
+    });
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_binds() {
+    block_on(async {
+        let pool = connect().await;
+        let json = serde_json::json!({ "name": "ovior", "tags": [1, 2] });
+        let id = insert(&pool, Row::new().field("json", json.clone()).build()).await;
+
+        assert_eq!(json, column::<serde_json::Value>(&pool, id, "json").await);
+    });
+}
+
+#[test]
+fn test_select_update() {
+    block_on(async {
+        let pool = connect().await;
+        for name in ["a", "b", "c"] {
+            insert(&pool, Row::new().field("string", name).build()).await;
+        }
+
+        let mut builder = Builder::table("binds");
+        builder.select("string").where_not_eq("string", "b");
+        // the builder keeps its bindings and can be executed again
+        for _ in 0..2 {
+            let rows = builder
+                .rows::<sqlx::Sqlite, _>(&pool)
+                .await
+                .expect("should select the rows");
+            assert_eq!(2, rows.len());
+        }
+
+        let mut builder = Builder::table("binds");
+        builder.where_not_eq("string", "b");
+        let updated = builder
+            .update::<sqlx::Sqlite, _, _>(&pool, Row::new().field("i64", 10).build())
+            .await
+            .expect("should update the rows");
+        assert!(updated);
+
+        let mut builder = Builder::table("binds");
+        builder
+            .select_raw("count(*)", Binds::None)
+            .where_eq("i64", 10);
+        let count = builder
+            .value::<sqlx::Sqlite, i64, _>(&pool)
+            .await
+            .expect("should count the rows");
+        assert_eq!(2, count);
+    });
+}
+
+#[test]
+fn test_full_and_lateral_join() {
+    block_on(async {
+        let pool = connect().await;
+        let a = insert(&pool, Row::new().field("string", "a").build()).await;
+        insert(&pool, Row::new().field("string", "b").build()).await;
+
+        let mut builder = Builder::table("binds as l");
+        builder
+            .select(["l.string", "r.string"])
+            .full_join("binds as r", "l.id", "=", "r.i64");
+        let rows = builder
+            .rows::<sqlx::Sqlite, _>(&pool)
+            .await
+            .expect("should run a full join");
+        assert_eq!(4, rows.len());
+
+        // both halves of the emulation keep the columns of `*` in place
+        let mut builder = Builder::table("binds as l");
+        builder.full_join("binds as r", "l.id", "=", "r.i64");
+        let rows = builder
+            .rows::<sqlx::Sqlite, _>(&pool)
+            .await
+            .expect("should run a full join");
+        assert_eq!(4, rows.len());
+        let strings = rows
+            .iter()
+            .map(|row| sqlx::Row::get::<Option<String>, _>(row, "string"))
+            .collect::<Vec<_>>();
+        assert_eq!(2, strings.iter().flatten().count());
+
+        let mut builder = Builder::table("binds as l");
+        builder.join_lateral(
+            |b: &mut Builder| {
+                b.select("id").from("binds").where_eq("id", a);
+            },
+            "r",
+            |_| {},
+        );
+        let Err(err) = builder.rows::<sqlx::Sqlite, _>(&pool).await else {
+            panic!("sqlite has no lateral joins");
+        };
+        assert!(err.to_string().contains("lateral"));
+    });
+}
+
+#[test]
+fn test_insert_many() {
+    block_on(async {
+        let pool = connect().await;
+        // two binds per row, sqlite takes at most 32766 per statement
+        let mut insert = InsertBuilder::insert_into("binds");
+        insert.rows((0..20_000i64).map(|i| {
+            move |row: &mut Row| {
+                row.field("i64", i).field("string", "bulk");
+            }
+        }));
+        assert_eq!(
+            2,
+            insert
+                .chunks_for(qraft::dialect::Dialect::Sqlite)
+                .unwrap()
+                .len()
+        );
+
+        let inserted = insert
+            .insert_many(&pool)
+            .await
+            .expect("should insert every chunk");
+        assert_eq!(20_000, inserted);
+        let inserted = insert
+            .execute_chunked::<sqlx::Sqlite, _>(&pool)
+            .await
+            .expect("should insert every chunk");
+        assert_eq!(20_000, inserted);
+
+        let mut builder = Builder::table("binds");
+        builder.select(qraft::count('*')).where_eq("string", "bulk");
+        let count: i64 = builder
+            .value::<sqlx::Sqlite, _, _>(&pool)
+            .await
+            .expect("should count the rows");
+        assert_eq!(40_000, count);
+    });
+}
+
+#[test]
+fn test_merge_as_upsert() {
+    block_on(async {
+        let pool = connect().await;
+        let a = insert(&pool, Row::new().field("string", "old").build()).await;
+        sqlx::query("create table staging (id integer, string text)")
+            .execute(&pool)
+            .await
+            .expect("should create the staging table");
+        sqlx::query("insert into staging values (?1, 'new'), (?2, 'added')")
+            .bind(a)
+            .bind(a + 1)
+            .execute(&pool)
+            .await
+            .expect("should fill the staging table");
+
+        let mut merge = qraft::MergeBuilder::merge_into("binds as b");
+        merge
+            .using("staging as s")
+            .on(|on| {
+                on.where_column("b.id", '=', "s.id");
+            })
+            .when_matched_update(|row: &mut Row| {
+                row.field("string", qraft::column("s.string"));
+            })
+            .when_not_matched_insert(|row: &mut Row| {
+                row.field("id", qraft::column("s.id"))
+                    .field("string", qraft::column("s.string"));
+            });
+        merge
+            .execute::<sqlx::Sqlite, _>(&pool)
+            .await
+            .expect("should run the merge as an upsert");
+
+        let mut builder = Builder::table("binds");
+        builder.select("string").order_by_asc("id");
+        let strings: Vec<(String,)> = builder
+            .all::<sqlx::Sqlite, _, _>(&pool)
+            .await
+            .expect("should read the merged rows");
+        assert_eq!(vec![("new".to_string(),), ("added".to_string(),)], strings);
+    });
+}
+
+#[test]
+fn test_update_from_join() {
+    block_on(async {
+        let pool = connect().await;
+        let a = insert(&pool, Row::new().field("string", "a").build()).await;
+        let b = insert(&pool, Row::new().field("string", "b").build()).await;
+        sqlx::query("create table flags (binds_id integer, flagged boolean)")
+            .execute(&pool)
+            .await
+            .expect("should create the flags table");
+        sqlx::query("insert into flags values (?1, true), (?2, false)")
+            .bind(a)
+            .bind(b)
+            .execute(&pool)
+            .await
+            .expect("should fill the flags table");
+
+        let mut builder = Builder::table("binds");
+        builder
+            .join("flags as f", "binds.id", "=", "f.binds_id")
+            .where_eq("f.flagged", true);
+        let updated = builder
+            .update::<sqlx::Sqlite, _, _>(&pool, Row::new().field("string", "flagged").build())
+            .await
+            .expect("should update from the joined table");
+        assert!(updated);
+        assert_eq!("flagged", column::<String>(&pool, a, "string").await);
+        assert_eq!("b", column::<String>(&pool, b, "string").await);
+
+        let mut builder = Builder::table("binds");
+        builder
+            .join("flags as f", "binds.id", "=", "f.binds_id")
+            .where_eq("f.flagged", false);
+        let deleted = builder
+            .delete::<sqlx::Sqlite, _>(&pool)
+            .await
+            .expect("should delete through the join");
+        assert!(deleted);
+
+        let mut builder = Builder::table("binds");
+        builder.select(qraft::count('*'));
+        let count: i64 = builder
+            .value::<sqlx::Sqlite, _, _>(&pool)
+            .await
+            .expect("should count the rows");
+        assert_eq!(1, count);
+    });
+}
+
+#[test]
+fn test_joined_mutation_with_raw_or() {
+    block_on(async {
+        let pool = connect().await;
+        let a = insert(&pool, Row::new().field("string", "a").build()).await;
+        let b = insert(&pool, Row::new().field("string", "b").build()).await;
+        let c = insert(&pool, Row::new().field("string", "c").build()).await;
+        sqlx::query("create table flags (binds_id integer, flagged boolean)")
+            .execute(&pool)
+            .await
+            .expect("should create the flags table");
+        sqlx::query("insert into flags values (?1, true), (?2, false)")
+            .bind(a)
+            .bind(b)
+            .execute(&pool)
+            .await
+            .expect("should fill the flags table");
+
+        // `c` has no flag, the `or` must stay inside the joined rows
+        let mut builder = Builder::table("binds");
+        builder
+            .join("flags as f", "binds.id", "=", "f.binds_id")
+            .where_raw("f.flagged = 0 or binds.string = 'c'", Binds::None);
+        builder
+            .update::<sqlx::Sqlite, _, _>(&pool, Row::new().field("string", "updated").build())
+            .await
+            .expect("should update from the joined table");
+        assert_eq!("a", column::<String>(&pool, a, "string").await);
+        assert_eq!("updated", column::<String>(&pool, b, "string").await);
+        assert_eq!("c", column::<String>(&pool, c, "string").await);
+
+        let mut builder = Builder::table("binds");
+        builder
+            .join("flags as f", "binds.id", "=", "f.binds_id")
+            .where_raw("f.flagged = 1 or binds.string = 'c'", Binds::None);
+        builder
+            .delete::<sqlx::Sqlite, _>(&pool)
+            .await
+            .expect("should delete through the join");
+
+        let mut builder = Builder::table("binds");
+        builder.select("id").order_by_asc("id");
+        let ids: Vec<(i64,)> = builder
+            .all::<sqlx::Sqlite, _, _>(&pool)
+            .await
+            .expect("should read the remaining rows");
+        assert_eq!(vec![(b,), (c,)], ids);
+    });
+}
+
+#[test]
+fn test_insert_get_id_leaves_the_insert() {
+    block_on(async {
+        let pool = connect().await;
+        let mut insert = InsertBuilder::insert_into("binds");
+        insert.row(Row::new().field("string", "a").build());
+        let sql = insert.to_sql::<sqlx::Sqlite>();
+        let id = insert
+            .insert_get_id::<sqlx::Sqlite, i64, _>(&pool)
+            .await
+            .expect("should insert the row");
+        assert_eq!(1, id);
+        assert_eq!(sql, insert.to_sql::<sqlx::Sqlite>());
+
+        let rows: Vec<(i64,)> = insert
+            .returning_all::<sqlx::Sqlite, _, _>(&pool)
+            .await
+            .expect("should insert the row again");
+        assert_eq!(1, rows.len());
+        assert_eq!(sql, insert.to_sql::<sqlx::Sqlite>());
+    });
+}